@@ -0,0 +1,410 @@
+use crate::*;
+use near_sdk::collections::UnorderedSet;
+use near_sdk::ext_contract;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AppMetadata {
+    pub name: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub icon: Option<String>,
+}
+
+/// which votable objects of an app can receive votes
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ObjectPolicy {
+    Open,         // any object id
+    OwnerManaged, // only the objects added by the app owner
+}
+
+/// A votable app (the `contract_address` of votes)
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VotableApp {
+    pub owner_id: AccountId,
+    pub metadata: AppMetadata,
+    pub object_policy: ObjectPolicy,
+    pub max_votes_per_object: Option<u128>, // cap of the total votes an object can receive
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VotableAppJSON {
+    pub contract_address: ContractAddress,
+    pub owner_id: AccountId,
+    pub metadata: AppMetadata,
+    pub object_policy: ObjectPolicy,
+    pub max_votes_per_object: Option<U128>,
+    pub archived: bool,
+}
+
+impl VotableApp {
+    fn to_json(&self, contract_address: ContractAddress) -> VotableAppJSON {
+        VotableAppJSON {
+            contract_address,
+            owner_id: self.owner_id.clone(),
+            metadata: self.metadata.clone(),
+            object_policy: self.object_policy.clone(),
+            max_votes_per_object: self.max_votes_per_object.map(U128::from),
+            archived: self.archived,
+        }
+    }
+}
+
+/// Interface of the apps listening to their vote changes
+#[allow(dead_code)]
+#[ext_contract(ext_votable_app)]
+pub trait VotableAppListener {
+    fn on_votes_changed(
+        &mut self,
+        voter_id: VoterId,
+        object_id: VotableObjId,
+        old_vp: U128,
+        new_vp: U128,
+    );
+}
+
+fn assert_valid_app_metadata(metadata: &AppMetadata) {
+    require!(!metadata.name.is_empty(), "App name is empty.");
+}
+
+impl MetaVoteContract {
+    fn internal_get_app_or_panic(&self, contract_address: &ContractAddress) -> VotableApp {
+        self.apps
+            .get(contract_address)
+            .expect("App not registered.")
+    }
+
+    /// the app owner or the contract owner
+    fn assert_app_owner(&self, app: &VotableApp) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            app.owner_id == predecessor || self.owner_id == predecessor,
+            "Only the app owner can call this function."
+        );
+    }
+
+    fn internal_save_app(&mut self, contract_address: &ContractAddress, app: &VotableApp) {
+        self.apps.insert(contract_address, app);
+        MetaVoteEvent::UpdateApp(AppData {
+            contract_address,
+            owner_id: app.owner_id.as_str(),
+            archived: app.archived,
+        })
+        .emit();
+    }
+
+    fn internal_get_app_objects(
+        &self,
+        contract_address: &ContractAddress,
+    ) -> UnorderedSet<VotableObjId> {
+        self.app_objects
+            .get(contract_address)
+            .unwrap_or(UnorderedSet::new(StorageKey::AppObjects {
+                hash_id: generate_hash_id(contract_address),
+            }))
+    }
+
+    /// fire-and-forget on_votes_changed to the app listener, if any.
+    /// The app cannot make the vote fail.
+    pub(crate) fn notify_votes_changed(
+        &self,
+        voter_id: &str,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+        old_vp: u128,
+        new_vp: u128,
+    ) {
+        if let Some(listener_id) = self.app_vote_listeners.get(contract_address) {
+            ext_votable_app::ext(listener_id.clone())
+                .with_static_gas(GAS_FOR_VOTES_CHANGED_NOTIFICATION)
+                .on_votes_changed(
+                    voter_id.to_string(),
+                    votable_object_id.clone(),
+                    old_vp.into(),
+                    new_vp.into(),
+                );
+        }
+    }
+
+    /// how much voting power the object can still receive, 0 if it cannot receive votes.
+    /// App rules only enforced when app_registry_enforced, delegations are not apps.
+    pub(crate) fn internal_vote_room(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) -> u128 {
+        if self.is_round_ended(contract_address) {
+            return 0;
+        }
+        if !self.app_registry_enforced || contract_address == DELEGATED_CONTRACT_CODE {
+            return u128::MAX;
+        }
+        self.internal_check_app_vote(contract_address, votable_object_id)
+            .unwrap_or_default()
+    }
+
+    /// panics if the object cannot receive voting_power more votes
+    pub(crate) fn assert_can_receive_votes(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+        voting_power: u128,
+    ) {
+        require!(
+            !self.is_round_ended(contract_address),
+            "The voting round ended, votes are frozen until it is finalized."
+        );
+        if !self.app_registry_enforced || contract_address == DELEGATED_CONTRACT_CODE {
+            return;
+        }
+        match self.internal_check_app_vote(contract_address, votable_object_id) {
+            Ok(room) => require!(
+                voting_power <= room,
+                format!(
+                    "Votes for this object are capped, it can receive {} more.",
+                    room
+                )
+            ),
+            Err(err) => env::panic_str(err),
+        }
+    }
+
+    fn internal_check_app_vote(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) -> Result<u128, &'static str> {
+        let app = self
+            .apps
+            .get(contract_address)
+            .ok_or("App not registered.")?;
+        if app.archived {
+            return Err("App is archived.");
+        }
+        if app.object_policy == ObjectPolicy::OwnerManaged
+            && !self
+                .internal_get_app_objects(contract_address)
+                .contains(votable_object_id)
+        {
+            return Err("Votable object not allowed by the app.");
+        }
+        Ok(match app.max_votes_per_object {
+            Some(max_votes) => {
+                let votes = self
+                    .votes
+                    .get(contract_address)
+                    .and_then(|votes_for_address| votes_for_address.get(votable_object_id))
+                    .unwrap_or(0);
+                max_votes.saturating_sub(votes)
+            }
+            None => u128::MAX,
+        })
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // ****************
+    // * App registry *
+    // ****************
+
+    #[payable]
+    pub fn register_app(
+        &mut self,
+        contract_address: ContractAddress,
+        owner_id: AccountId,
+        metadata: AppMetadata,
+        object_policy: ObjectPolicy,
+        max_votes_per_object: Option<U128>,
+    ) {
+        assert_one_yocto();
+        self.assert_owner_or_mpip();
+        require!(
+            contract_address != DELEGATED_CONTRACT_CODE,
+            "Reserved contract address."
+        );
+        require!(
+            self.apps.get(&contract_address).is_none(),
+            "App already registered."
+        );
+        assert_valid_app_metadata(&metadata);
+        let app = VotableApp {
+            owner_id,
+            metadata,
+            object_policy,
+            max_votes_per_object: max_votes_per_object.map(|max_votes| max_votes.0),
+            archived: false,
+        };
+        self.apps.insert(&contract_address, &app);
+        MetaVoteEvent::RegisterApp(AppData {
+            contract_address: &contract_address,
+            owner_id: app.owner_id.as_str(),
+            archived: false,
+        })
+        .emit();
+    }
+
+    /// app owner: update metadata, object policy and vote cap
+    #[payable]
+    pub fn update_app(
+        &mut self,
+        contract_address: ContractAddress,
+        metadata: AppMetadata,
+        object_policy: ObjectPolicy,
+        max_votes_per_object: Option<U128>,
+    ) {
+        assert_one_yocto();
+        let mut app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        assert_valid_app_metadata(&metadata);
+        app.metadata = metadata;
+        app.object_policy = object_policy;
+        app.max_votes_per_object = max_votes_per_object.map(|max_votes| max_votes.0);
+        self.internal_save_app(&contract_address, &app);
+    }
+
+    #[payable]
+    pub fn set_app_owner(&mut self, contract_address: ContractAddress, owner_id: AccountId) {
+        assert_one_yocto();
+        let mut app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        app.owner_id = owner_id;
+        self.internal_save_app(&contract_address, &app);
+    }
+
+    /// archived apps cannot receive new votes, current votes can be removed
+    #[payable]
+    pub fn set_app_archived(&mut self, contract_address: ContractAddress, archived: bool) {
+        assert_one_yocto();
+        let mut app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        app.archived = archived;
+        self.internal_save_app(&contract_address, &app);
+    }
+
+    /// objects that can receive votes in OwnerManaged apps
+    #[payable]
+    pub fn add_app_objects(
+        &mut self,
+        contract_address: ContractAddress,
+        votable_object_ids: Vec<VotableObjId>,
+    ) {
+        assert_one_yocto();
+        let app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        let mut objects = self.internal_get_app_objects(&contract_address);
+        for votable_object_id in votable_object_ids.iter() {
+            objects.insert(votable_object_id);
+        }
+        self.app_objects.insert(&contract_address, &objects);
+        MetaVoteEvent::AddAppObjects(AppObjectsData {
+            contract_address: &contract_address,
+            votable_object_ids: &votable_object_ids,
+        })
+        .emit();
+    }
+
+    /// current votes for removed objects are kept, they cannot receive more votes
+    #[payable]
+    pub fn remove_app_objects(
+        &mut self,
+        contract_address: ContractAddress,
+        votable_object_ids: Vec<VotableObjId>,
+    ) {
+        assert_one_yocto();
+        let app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        let mut objects = self.internal_get_app_objects(&contract_address);
+        for votable_object_id in votable_object_ids.iter() {
+            objects.remove(votable_object_id);
+        }
+        self.app_objects.insert(&contract_address, &objects);
+        MetaVoteEvent::RemoveAppObjects(AppObjectsData {
+            contract_address: &contract_address,
+            votable_object_ids: &votable_object_ids,
+        })
+        .emit();
+    }
+
+    /// app owner: the account receiving on_votes_changed(voter_id, object_id, old_vp, new_vp)
+    /// after each vote change in the app. None stops the notifications.
+    #[payable]
+    pub fn set_app_vote_listener(
+        &mut self,
+        contract_address: ContractAddress,
+        listener_id: Option<AccountId>,
+    ) {
+        assert_one_yocto();
+        let app = self.internal_get_app_or_panic(&contract_address);
+        self.assert_app_owner(&app);
+        emit_config_change(
+            "app_vote_listener",
+            format!("{} {:?}", contract_address, listener_id),
+        );
+        match listener_id {
+            Some(listener_id) => {
+                self.app_vote_listeners
+                    .insert(contract_address, listener_id);
+            }
+            None => {
+                self.app_vote_listeners.remove(&contract_address);
+            }
+        }
+    }
+
+    pub fn get_app_vote_listener(&self, contract_address: ContractAddress) -> Option<AccountId> {
+        self.app_vote_listeners.get(&contract_address).cloned()
+    }
+
+    /// when enforced, votes to unregistered or archived apps are rejected
+    #[payable]
+    pub fn set_app_registry_enforced(&mut self, enforced: bool) {
+        assert_one_yocto();
+        self.assert_owner_or_mpip();
+        emit_config_change("app_registry_enforced", enforced);
+        self.app_registry_enforced = enforced;
+    }
+
+    pub fn is_app_registry_enforced(&self) -> bool {
+        self.app_registry_enforced
+    }
+
+    pub fn get_app(&self, contract_address: ContractAddress) -> Option<VotableAppJSON> {
+        self.apps
+            .get(&contract_address)
+            .map(|app| app.to_json(contract_address))
+    }
+
+    pub fn get_apps(&self, from_index: u32, limit: u32) -> Vec<VotableAppJSON> {
+        let keys = self.apps.keys_as_vector();
+        let start = from_index as u64;
+        let end = std::cmp::min(start + limit as u64, keys.len());
+        (start..end)
+            .map(|index| {
+                let contract_address = keys.get(index).unwrap();
+                self.apps
+                    .get(&contract_address)
+                    .unwrap()
+                    .to_json(contract_address)
+            })
+            .collect()
+    }
+
+    pub fn get_app_objects(
+        &self,
+        contract_address: ContractAddress,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<VotableObjId> {
+        self.internal_get_app_objects(&contract_address)
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
@@ -113,6 +113,10 @@ impl MetaVoteContract {
                 amount_received,
             },
         );
+        emit_config_change(
+            "token_info",
+            format!("{} decimals:{}", token_address, token_decimals),
+        );
     }
 
     /// enable/disable a token that can be used to buy mpDAO
@@ -122,6 +126,10 @@ impl MetaVoteContract {
         if let Some(mut token_info) = self.token_info.get(token_address) {
             token_info.enabled = enabled;
             self.token_info.insert(token_address, &token_info);
+            emit_config_change(
+                "token_info",
+                format!("{} enabled:{}", token_address, enabled),
+            );
         } else {
             env::panic_str("Token not found");
         }
@@ -154,6 +162,7 @@ impl MetaVoteContract {
         assert_one_yocto();
         self.assert_operator();
         self.mpdao_prices.clear();
+        MetaVoteEvent::DeleteAllPrices.emit();
     }
     /// batch update prices for tokens configured
     #[payable]
@@ -180,6 +189,12 @@ impl MetaVoteContract {
                     paused: price.paused,
                 },
             );
+            MetaVoteEvent::UpdatePrice(PriceData {
+                token_contract: price.token_contract.as_str(),
+                mpdao_per_token_e9: price.mpdao_per_token_e9,
+                paused: price.paused,
+            })
+            .emit();
         }
     }
 
@@ -269,13 +284,15 @@ impl MetaVoteContract {
         token_info.amount_received += token_and_amount.amount;
         self.token_info.insert(&token_and_amount.token, &token_info);
 
-        log!(
-            "{} bought {} mpDAO with {} {}",
-            sender_id,
-            mpdao_amount,
-            token_and_amount.amount,
-            token_and_amount.token
-        );
+        MetaVoteEvent::BuyAndLock(BuyAndLockData {
+            account_id: sender_id.as_str(),
+            beneficiary_id: options.beneficiary.as_deref().unwrap_or(sender_id.as_str()),
+            token_contract: token_and_amount.token.as_str(),
+            token_amount: token_and_amount.amount.into(),
+            mpdao_amount: mpdao_amount.into(),
+            locking_period: options.days,
+        })
+        .emit();
 
         self.lock_and_optionally_vote(sender_id, mpdao_amount, &options);
     }
@@ -289,22 +306,16 @@ impl MetaVoteContract {
     ) {
         // lock for sender or others
         let voter_id = options.beneficiary.clone().unwrap_or(sender_id.to_string());
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter(&voter_id);
         self.deposit_locking_position(mpdao_amount, options.days, &voter_id, &mut voter);
 
-        log!(
-            "{} locked {} mpDAO {} days",
-            voter_id,
-            mpdao_amount,
-            options.days
-        );
-
         // if it is also a vote command, vote
         if let (Some(contract_address), Some(votable_object_id)) = (
             options.contract_address.clone(),
             options.votable_object_id.clone(),
         ) {
-            let voting_power = utils::calculate_voting_power(mpdao_amount, options.days);
+            let voting_power = self.internal_calculate_voting_power(mpdao_amount, options.days);
             self.internal_vote(
                 &voter_id,
                 voting_power.into(),
@@ -318,6 +329,7 @@ impl MetaVoteContract {
     pub fn update_mpdao_avail_to_sell(&mut self, mpdao_avail_to_sell: U128String) {
         self.assert_only_owner();
         self.mpdao_avail_to_sell = mpdao_avail_to_sell.0;
+        emit_config_change("mpdao_avail_to_sell", self.mpdao_avail_to_sell);
     }
 
     // If extra NEAR balance (from buy_lock_and_vote with NEAR)
@@ -0,0 +1,123 @@
+use crate::*;
+use near_sdk::json_types::U64;
+
+/// Voting power from `timestamp` on, until the next checkpoint
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VpCheckpoint {
+    pub timestamp: EpochMillis,
+    pub voting_power: u128,
+}
+
+impl MetaVoteContract {
+    // ***************************
+    // * Voting power checkpoints *
+    // ***************************
+
+    /// MPIP voting power: self vp + delegated vp received - vp delegated away
+    pub(crate) fn internal_get_mpip_vp(&self, voter_id: &String, voter: &Voter) -> u128 {
+        // App scoped delegations are not MPIP voting power of the delegate
+        let received_vp = self
+            .internal_get_delegated_vp(voter_id)
+            .saturating_sub(voter.sum_app_scoped_delegated_vp());
+        voter
+            .sum_locked_vp()
+            .saturating_add(received_vp)
+            .saturating_sub(self.internal_mpip_delegated_away_vp(voter_id, voter))
+    }
+
+    /// push a new checkpoint, or replace the last one if it is from the same block timestamp
+    fn push_checkpoint(checkpoints: &mut Vector<VpCheckpoint>, voting_power: u128) {
+        let now = env::block_timestamp_ms();
+        let new_checkpoint = VpCheckpoint {
+            timestamp: now,
+            voting_power,
+        };
+        if let Some(last_index) = checkpoints.len().checked_sub(1) {
+            let last = checkpoints.get(last_index).unwrap();
+            if last.voting_power == voting_power {
+                return;
+            }
+            if last.timestamp == now {
+                checkpoints.replace(last_index, &new_checkpoint);
+                return;
+            }
+        }
+        checkpoints.push(&new_checkpoint);
+    }
+
+    /// binary search for the last checkpoint with checkpoint.timestamp <= timestamp
+    fn find_checkpoint_vp(checkpoints: &Vector<VpCheckpoint>, timestamp: EpochMillis) -> u128 {
+        let mut low = 0_u64;
+        let mut high = checkpoints.len();
+        while low < high {
+            let mid = (low + high) / 2;
+            if checkpoints.get(mid).unwrap().timestamp <= timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            0
+        } else {
+            checkpoints.get(low - 1).unwrap().voting_power
+        }
+    }
+
+    fn internal_get_voter_checkpoints(&self, voter_id: &String) -> Vector<VpCheckpoint> {
+        self.vp_checkpoints
+            .get(voter_id)
+            .unwrap_or(Vector::new(StorageKey::VoterVpCheckpoints {
+                hash_id: generate_hash_id(voter_id),
+            }))
+    }
+
+    /// call this before changing the voting power of a voter, before loading the voter.
+    /// Voters existing before the checkpoints get a first checkpoint at timestamp 0
+    /// with their voting power, unchanged until now.
+    pub(crate) fn internal_seed_vp_checkpoints(&mut self, voter_id: &String) {
+        if self.vp_checkpoints.get(voter_id).is_some() {
+            return;
+        }
+        if let Some(voter) = self.voters.get(voter_id) {
+            let mut checkpoints = self.internal_get_voter_checkpoints(voter_id);
+            checkpoints.push(&VpCheckpoint {
+                timestamp: 0,
+                voting_power: self.internal_get_mpip_vp(voter_id, &voter),
+            });
+            self.vp_checkpoints.insert(voter_id, &checkpoints);
+        }
+    }
+
+    /// call this after changing the delegations of a voter, for locking positions
+    /// changes call internal_after_vp_change.
+    /// Records the voter's mpip voting power and the contract total voting power.
+    pub(crate) fn write_vp_checkpoints(&mut self, voter_id: &String, voter: &Voter) {
+        let mut checkpoints = self.internal_get_voter_checkpoints(voter_id);
+        Self::push_checkpoint(&mut checkpoints, self.internal_get_mpip_vp(voter_id, voter));
+        self.vp_checkpoints.insert(voter_id, &checkpoints);
+        Self::push_checkpoint(&mut self.total_vp_checkpoints, self.total_voting_power);
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// MPIP voting power of a voter at a timestamp in the past (snapshot voting).
+    /// A voter without checkpoints has had the same voting power since the checkpoints exist.
+    pub fn get_mpip_voting_power_at(&self, voter_id: VoterId, timestamp: U64) -> U128String {
+        match self.vp_checkpoints.get(&voter_id) {
+            Some(checkpoints) => Self::find_checkpoint_vp(&checkpoints, timestamp.0).into(),
+            None => self
+                .voters
+                .get(&voter_id)
+                .map(|voter| self.internal_get_mpip_vp(&voter_id, &voter))
+                .unwrap_or(0)
+                .into(),
+        }
+    }
+
+    /// all users accumulated vp at a timestamp in the past
+    pub fn get_total_voting_power_at(&self, timestamp: U64) -> U128String {
+        Self::find_checkpoint_vp(&self.total_vp_checkpoints, timestamp.0).into()
+    }
+}
@@ -12,9 +12,25 @@ pub fn near_as_account_id() -> AccountId {
 pub const SECONDS_IN_MS: u64 = 1000;
 pub const MINUTES_IN_MS: u64 = 60 * SECONDS_IN_MS;
 
+pub const MAX_VP_CURVE_POINTS: usize = 20;
+pub const MAX_VOTE_ALLOCATIONS: usize = 50;
+pub const MAX_DELEGATES: usize = 10;
+pub const MAX_EXPIRIES_PER_CALL: usize = 50; // expired delegations returned per delegate and call
+pub const MAX_DELEGATE_NAME_LEN: usize = 64;
+pub const MAX_DELEGATE_STATEMENT_LEN: usize = 256; // statement_url and statement_hash
+pub const MAX_DELEGATE_ACCEPTED_SCOPES: usize = 10;
+pub const MAX_ACCOUNT_ID_LEN: usize = 64;
+
 /// Amount of gas for fungible token transfers.
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas(47 * TGAS);
 pub const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(11 * TGAS);
+/// Amount of gas for nft_transfer_call, resolving can move the position back and trim votes.
+pub const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas(25 * TGAS);
+pub const GAS_FOR_RESOLVE_NFT_TRANSFER: Gas = Gas(40 * TGAS);
+/// Gas left for the bounty transfer and state write after sweeping stale votes.
+pub const GAS_RESERVE_FOR_STALE_SWEEP: Gas = Gas(20 * TGAS);
+/// Gas attached to on_votes_changed notifications, fire-and-forget.
+pub const GAS_FOR_VOTES_CHANGED_NOTIFICATION: Gas = Gas(5 * TGAS);
 
 /// IMPORTANT 🚨: DO NOT REORDER OR REMOVE VARIANTS.
 /// APPEND NEW VARIANTS ONLY AT THE END.
@@ -36,5 +52,31 @@ pub enum StorageKey {
     TimestampStorage,
     TokenInfo,
     MpdaoPrices,
-    ClaimableUnlockedMpdao
+    ClaimableUnlockedMpdao,
+    VpCheckpoints,
+    VoterVpCheckpoints { hash_id: CryptoHash },
+    TotalVpCheckpoints,
+    MerkleDistributions,
+    MerkleClaimedBitmap,
+    PositionNfts,
+    PositionNftTokenIds,
+    VoteAllocationsBp,
+    Apps,
+    AppObjectLists,
+    AppObjects { hash_id: CryptoHash },
+    VotingRounds,
+    ActiveVotingRounds,
+    RoundVoterVotes,
+    RoundResults { round_id: u32 },
+    StalePolicies,
+    AppVoteListeners,
+    ObjectVoterLists,
+    ObjectVoters { hash_id: CryptoHash },
+    DelegationsBp,
+    DelegationExpiries,
+    DelegateProfiles,
+    DelegationScopes,
+    EvmDelegationNonces,
+    DelegationExpiryQueue,
+    DelegatesByVp,
 }
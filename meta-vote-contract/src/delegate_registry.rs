@@ -0,0 +1,251 @@
+use crate::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// what a delegate can do with the delegated voting power
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DelegationScope {
+    All,                                       // any vote and MPIPs
+    Mpip,                                      // only MPIPs voting power
+    App { contract_address: ContractAddress }, // only votes in the app, e.g. "metastaking.app"
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DelegateStatus {
+    Open,   // accepting delegations
+    Paused, // not accepting new delegations
+    Closed, // not accepting new delegations, not listed
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegateProfile {
+    pub name: String,
+    pub statement_url: Option<String>,
+    pub statement_hash: Option<String>, // e.g. sha256 of the statement
+    pub accepted_scopes: Vec<DelegationScope>,
+    pub status: DelegateStatus,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RegisteredDelegateJSON {
+    pub delegate_id: VoterId,
+    pub profile: DelegateProfile,
+    pub delegated_voting_power: U128,
+}
+
+impl MetaVoteContract {
+    /// registered, open and accepting the scope. Always true when the registry is not enforced.
+    pub(crate) fn delegate_accepts(&self, delegate_id: &String, scope: &DelegationScope) -> bool {
+        if !self.delegate_registry_enforced {
+            return true;
+        }
+        self.delegate_profiles
+            .get(delegate_id)
+            .map(|profile| {
+                profile.status == DelegateStatus::Open
+                    && profile
+                        .accepted_scopes
+                        .iter()
+                        .any(|accepted| accepted == &DelegationScope::All || accepted == scope)
+            })
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn assert_delegate_accepts(&self, delegate_id: &String, scope: &DelegationScope) {
+        require!(
+            self.delegate_accepts(delegate_id, scope),
+            format!(
+                "{} is not a registered delegate accepting {:?} delegations.",
+                delegate_id, scope
+            )
+        );
+    }
+
+    fn is_listed_delegate(&self, delegate_id: &String) -> bool {
+        self.delegate_profiles
+            .get(delegate_id)
+            .map(|profile| profile.status != DelegateStatus::Closed)
+            .unwrap_or(false)
+    }
+
+    /// moves a listed delegate in the delegates by voting power index
+    pub(crate) fn internal_update_delegate_rank(
+        &mut self,
+        delegate_id: &String,
+        old_vp: u128,
+        new_vp: u128,
+    ) {
+        if old_vp == new_vp || !self.is_listed_delegate(delegate_id) {
+            return;
+        }
+        self.delegates_by_vp.remove(&(old_vp, delegate_id.clone()));
+        self.delegates_by_vp
+            .insert(&(new_vp, delegate_id.clone()), &());
+    }
+
+    /// lists open & paused delegates, call after changing the delegate status
+    fn internal_update_delegate_listing(&mut self, delegate_id: &String, was_listed: bool) {
+        let key = (
+            self.internal_get_delegated_vp(delegate_id),
+            delegate_id.clone(),
+        );
+        let listed = self.is_listed_delegate(delegate_id);
+        if was_listed && !listed {
+            self.delegates_by_vp.remove(&key);
+        } else if listed && !was_listed {
+            self.delegates_by_vp.insert(&key, &());
+        }
+    }
+
+    fn internal_delegate_json(
+        &self,
+        delegate_id: VoterId,
+        profile: DelegateProfile,
+    ) -> RegisteredDelegateJSON {
+        RegisteredDelegateJSON {
+            delegated_voting_power: self.internal_get_delegated_vp(&delegate_id).into(),
+            delegate_id,
+            profile,
+        }
+    }
+}
+
+fn assert_valid_delegate_profile(profile: &DelegateProfile) {
+    require!(!profile.name.is_empty(), "Delegate name is empty.");
+    require!(
+        profile.name.len() <= MAX_DELEGATE_NAME_LEN,
+        format!("Delegate name longer than {} bytes.", MAX_DELEGATE_NAME_LEN)
+    );
+    for statement in [&profile.statement_url, &profile.statement_hash]
+        .into_iter()
+        .flatten()
+    {
+        require!(
+            statement.len() <= MAX_DELEGATE_STATEMENT_LEN,
+            format!(
+                "Statement url and hash cannot be longer than {} bytes.",
+                MAX_DELEGATE_STATEMENT_LEN
+            )
+        );
+    }
+    require!(
+        !profile.accepted_scopes.is_empty(),
+        "The delegate must accept at least one scope."
+    );
+    require!(
+        profile.accepted_scopes.len() <= MAX_DELEGATE_ACCEPTED_SCOPES,
+        format!(
+            "Cannot accept more than {} scopes.",
+            MAX_DELEGATE_ACCEPTED_SCOPES
+        )
+    );
+    for scope in profile.accepted_scopes.iter() {
+        assert_valid_delegation_scope(scope);
+    }
+}
+
+pub(crate) fn assert_valid_delegation_scope(scope: &DelegationScope) {
+    if let DelegationScope::App { contract_address } = scope {
+        require!(
+            contract_address.len() <= MAX_ACCOUNT_ID_LEN,
+            "Invalid app contract address."
+        );
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // *********************
+    // * Delegate registry *
+    // *********************
+
+    /// registers or updates the caller delegate profile,
+    /// attach the storage cost of the profile (the rest is returned)
+    #[payable]
+    pub fn register_delegate(&mut self, profile: DelegateProfile) {
+        let initial_storage_usage = env::storage_usage();
+        let delegate_id = env::predecessor_account_id().as_str().to_string();
+        require!(
+            self.voters.get(&delegate_id).is_some(),
+            "Only voters can be delegates."
+        );
+        require!(
+            !self.is_delegator(&delegate_id),
+            "Delegators cannot be delegates."
+        );
+        assert_valid_delegate_profile(&profile);
+        let was_listed = self.is_listed_delegate(&delegate_id);
+        self.delegate_profiles.insert(&delegate_id, &profile);
+        self.internal_update_delegate_listing(&delegate_id, was_listed);
+        MetaVoteEvent::RegisterDelegate(DelegateData {
+            account_id: &delegate_id,
+            status: &profile.status,
+        })
+        .emit();
+        charge_storage_increase(initial_storage_usage);
+    }
+
+    /// pause or close the caller delegations, current delegations are kept
+    pub fn set_delegate_status(&mut self, status: DelegateStatus) {
+        let delegate_id = env::predecessor_account_id().as_str().to_string();
+        let mut profile = self
+            .delegate_profiles
+            .get(&delegate_id)
+            .expect("Delegate not registered.");
+        let was_listed = profile.status != DelegateStatus::Closed;
+        profile.status = status;
+        self.delegate_profiles.insert(&delegate_id, &profile);
+        self.internal_update_delegate_listing(&delegate_id, was_listed);
+        MetaVoteEvent::UpdateDelegate(DelegateData {
+            account_id: &delegate_id,
+            status: &profile.status,
+        })
+        .emit();
+    }
+
+    /// when enforced, new delegations require a registered delegate accepting them
+    #[payable]
+    pub fn set_delegate_registry_enforced(&mut self, enforced: bool) {
+        assert_one_yocto();
+        self.assert_owner_or_mpip();
+        emit_config_change("delegate_registry_enforced", enforced);
+        self.delegate_registry_enforced = enforced;
+    }
+
+    pub fn is_delegate_registry_enforced(&self) -> bool {
+        self.delegate_registry_enforced
+    }
+
+    pub fn get_registered_delegate(&self, delegate_id: VoterId) -> Option<RegisteredDelegateJSON> {
+        self.delegate_profiles
+            .get(&delegate_id)
+            .map(|profile| self.internal_delegate_json(delegate_id, profile))
+    }
+
+    /// open & paused delegates sorted by delegated voting power, highest first.
+    /// Closed delegates are not listed.
+    pub fn get_registered_delegates(
+        &self,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<RegisteredDelegateJSON> {
+        self.delegates_by_vp
+            .iter_rev()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|((_, delegate_id), _)| {
+                let profile = self.delegate_profiles.get(&delegate_id).unwrap();
+                self.internal_delegate_json(delegate_id, profile)
+            })
+            .collect()
+    }
+
+    /// open & paused delegates count
+    pub fn get_registered_delegates_count(&self) -> u64 {
+        self.delegates_by_vp.len()
+    }
+}
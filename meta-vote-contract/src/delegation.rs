@@ -0,0 +1,653 @@
+use crate::delegate_registry::DelegationScope;
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+use std::ops::Bound;
+
+/// A delegation stored as basis points of the delegator locked voting power
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegationBp {
+    pub delegate_id: AccountId,
+    pub bp: u16,
+}
+
+impl MetaVoteContract {
+    fn internal_get_delegation_expiry(
+        &self,
+        delegator_id: &str,
+        delegate_id: &str,
+    ) -> Option<EpochMillis> {
+        self.delegation_expiries
+            .get(&(delegator_id.to_string(), delegate_id.to_string()))
+            .copied()
+    }
+
+    pub(crate) fn is_delegation_expired(&self, delegator_id: &str, delegate_id: &str) -> bool {
+        self.internal_get_delegation_expiry(delegator_id, delegate_id)
+            .map(|expires_at| env::block_timestamp_ms() >= expires_at)
+            .unwrap_or(false)
+    }
+
+    /// call it when a delegation is removed
+    pub(crate) fn internal_remove_delegation_expiry(
+        &mut self,
+        delegator_id: &str,
+        delegate_id: &str,
+    ) {
+        if let Some(expires_at) = self
+            .delegation_expiries
+            .remove(&(delegator_id.to_string(), delegate_id.to_string()))
+        {
+            self.delegation_expiry_queue.remove(&(
+                delegate_id.to_string(),
+                expires_at,
+                delegator_id.to_string(),
+            ));
+        }
+    }
+
+    fn internal_insert_delegation_expiry(
+        &mut self,
+        delegator_id: &str,
+        delegate_id: &str,
+        expires_at: EpochMillis,
+    ) {
+        self.delegation_expiries.insert(
+            (delegator_id.to_string(), delegate_id.to_string()),
+            expires_at,
+        );
+        self.delegation_expiry_queue.insert(
+            &(
+                delegate_id.to_string(),
+                expires_at,
+                delegator_id.to_string(),
+            ),
+            &(),
+        );
+    }
+
+    /// the min hold of the delegation ends, counted from the last delegation change
+    fn internal_delegation_hold_end(&self, delegator_id: &String, delegate_id: &String) -> u64 {
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        self.get_vote_timestamp(delegator_id, &contract_address, delegate_id)
+            + self.internal_min_hold_ms(&contract_address)
+    }
+
+    /// an expiry cannot return the voting power before the min hold ends: when the delegation
+    /// changed after the expiry was set, the expiry is postponed to the end of the hold.
+    /// Returns true if the delegation can expire now.
+    fn internal_postpone_expiry_to_min_hold(
+        &mut self,
+        delegator_id: &String,
+        delegate_id: &String,
+    ) -> bool {
+        let hold_end = self.internal_delegation_hold_end(delegator_id, delegate_id);
+        if env::block_timestamp_ms() >= hold_end {
+            return true;
+        }
+        self.internal_remove_delegation_expiry(delegator_id, delegate_id);
+        self.internal_insert_delegation_expiry(delegator_id, delegate_id, hold_end);
+        false
+    }
+
+    /// delegators of the expired delegations received by the delegate, oldest expiry first
+    fn internal_get_expired_delegators(&self, delegate_id: &str, limit: usize) -> Vec<VoterId> {
+        let now = env::block_timestamp_ms();
+        self.delegation_expiry_queue
+            .range((
+                Bound::Included((delegate_id.to_string(), 0, String::new())),
+                Bound::Excluded((delegate_id.to_string(), now + 1, String::new())),
+            ))
+            .take(limit)
+            .map(|((_, _, delegator_id), _)| delegator_id)
+            .collect()
+    }
+
+    /// returns the voting power of the delegation to the delegator and
+    /// trims the delegate votes exceeding their new voting power
+    fn internal_expire_delegation(
+        &mut self,
+        delegator_id: &String,
+        delegator: &mut Voter,
+        delegate_id: &String,
+    ) {
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        if let Some(mut delegations) = self.delegations_bp.get(delegator_id).cloned() {
+            delegations.retain(|delegation| delegation.delegate_id.as_str() != delegate_id);
+            if delegations.is_empty() {
+                self.delegations_bp.remove(delegator_id);
+            } else {
+                self.delegations_bp
+                    .insert(delegator_id.clone(), delegations);
+            }
+        }
+        self.internal_remove_voting_position(
+            delegator_id,
+            delegator,
+            &contract_address,
+            delegate_id,
+        );
+        if let Some(mut delegate) = self.voters.get(delegate_id) {
+            self.adjust_voter_voting_power(delegate_id, &mut delegate);
+            self.voters.insert(delegate_id, &delegate);
+        }
+        MetaVoteEvent::DelegationExpired(DelegationData {
+            account_id: delegator_id,
+            delegate_id,
+        })
+        .emit();
+    }
+
+    /// delegator side: expires the voter expired delegations. Returns true if any.
+    pub(crate) fn internal_expire_delegations_of(
+        &mut self,
+        voter_id: &String,
+        voter: &mut Voter,
+    ) -> bool {
+        let expired: Vec<String> = match voter
+            .vote_positions
+            .get(&DELEGATED_CONTRACT_CODE.to_string())
+        {
+            Some(votes_for_address) => votes_for_address
+                .keys()
+                .filter(|delegate_id| self.is_delegation_expired(voter_id, delegate_id))
+                .collect(),
+            None => return false,
+        };
+        let mut any_expired = false;
+        for delegate_id in expired.iter() {
+            if self.internal_postpone_expiry_to_min_hold(voter_id, delegate_id) {
+                self.internal_expire_delegation(voter_id, voter, delegate_id);
+                any_expired = true;
+            }
+        }
+        any_expired
+    }
+
+    /// delegate side: expires up to MAX_EXPIRIES_PER_CALL delegations received by the delegate.
+    /// The delegate Voter must not be loaded by the caller. Returns the expired count.
+    fn internal_expire_delegations_to(&mut self, delegate_id: &String) -> u32 {
+        let expired = self.internal_get_expired_delegators(delegate_id, MAX_EXPIRIES_PER_CALL);
+        let mut count = 0;
+        for delegator_id in expired.iter() {
+            match self.voters.get(delegator_id) {
+                Some(mut delegator) => {
+                    if !self.internal_postpone_expiry_to_min_hold(delegator_id, delegate_id) {
+                        continue;
+                    }
+                    self.internal_seed_vp_checkpoints(delegator_id);
+                    self.internal_expire_delegation(delegator_id, &mut delegator, delegate_id);
+                    self.voters.insert(delegator_id, &delegator);
+                    count += 1;
+                }
+                None => self.internal_remove_delegation_expiry(delegator_id, delegate_id),
+            }
+        }
+        count
+    }
+
+    /// lazy expiry, call it before loading the voter
+    pub(crate) fn internal_touch_delegations(&mut self, voter_id: &String) {
+        self.internal_seed_vp_checkpoints(voter_id);
+        self.internal_expire_delegations_to(voter_id);
+        if let Some(mut voter) = self.voters.get(voter_id) {
+            if self.internal_expire_delegations_of(voter_id, &mut voter) {
+                self.voters.insert(voter_id, &voter);
+            }
+        }
+    }
+
+    /// the delegate has delegated to someone else, cannot receive delegations (no chains)
+    pub(crate) fn is_delegator(&self, voter_id: &String) -> bool {
+        self.voters
+            .get(voter_id)
+            .map(|voter| {
+                voter
+                    .vote_positions
+                    .get(&DELEGATED_CONTRACT_CODE.to_string())
+                    .is_some()
+            })
+            .unwrap_or(false)
+    }
+
+    /// redelegate: moves the share of from_votes being moved from one delegate basis points
+    /// to the other, so the next re-materialization keeps the move
+    fn internal_move_delegation_bp(
+        &mut self,
+        voter_id: &String,
+        from_delegate: &String,
+        to_delegate: &String,
+        voting_power: u128,
+        from_votes: u128,
+    ) {
+        let mut delegations = match self.delegations_bp.get(voter_id) {
+            Some(delegations) => delegations.clone(),
+            None => return,
+        };
+        let from_bp = match delegations
+            .iter()
+            .find(|delegation| delegation.delegate_id.as_str() == from_delegate)
+        {
+            Some(delegation) => delegation.bp,
+            None => return,
+        };
+        let moved_bp = if voting_power == from_votes {
+            from_bp
+        } else {
+            (from_bp as u128 * voting_power / from_votes) as u16
+        };
+        for delegation in delegations.iter_mut() {
+            if delegation.delegate_id.as_str() == from_delegate {
+                delegation.bp -= moved_bp;
+            }
+        }
+        match delegations
+            .iter_mut()
+            .find(|delegation| delegation.delegate_id.as_str() == to_delegate)
+        {
+            Some(delegation) => delegation.bp += moved_bp,
+            None => {
+                require!(
+                    delegations.len() < MAX_DELEGATES,
+                    format!("Cannot exceed {} delegates.", MAX_DELEGATES)
+                );
+                delegations.push(DelegationBp {
+                    delegate_id: to_delegate.parse().unwrap(),
+                    bp: moved_bp,
+                });
+            }
+        }
+        delegations.retain(|delegation| delegation.bp > 0);
+        self.delegations_bp.insert(voter_id.clone(), delegations);
+    }
+
+    /// re-materializes the delegations of a voter with a delegation config.
+    /// Decreases first, increases are capped to the available voting power.
+    /// Voters receiving delegations cannot delegate, their config waits.
+    pub(crate) fn internal_follow_delegation(&mut self, voter_id: &String, voter: &mut Voter) {
+        let delegations = match self.delegations_bp.get(voter_id) {
+            Some(delegations) => delegations.clone(),
+            None => return,
+        };
+        if self.internal_get_delegated_vp(voter_id) > 0 {
+            return;
+        }
+        self.update_vp_available(voter_id, voter);
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        let locked_voting_power = voter.sum_locked_vp();
+        let targets: Vec<(String, u128, u128)> = delegations
+            .iter()
+            .map(|delegation| {
+                let delegate_id = delegation.delegate_id.to_string();
+                let votes = voter
+                    .vote_positions
+                    .get(&contract_address)
+                    .and_then(|votes_for_address| votes_for_address.get(&delegate_id))
+                    .unwrap_or(0);
+                (
+                    delegate_id,
+                    votes,
+                    apply_bp(locked_voting_power, delegation.bp),
+                )
+            })
+            .collect();
+
+        for (delegate_id, votes, target) in targets.iter() {
+            if target >= votes {
+                continue;
+            }
+            if *target == 0 {
+                self.internal_remove_voting_position(
+                    voter_id,
+                    voter,
+                    &contract_address,
+                    delegate_id,
+                );
+            } else {
+                self.internal_rebalance_voting_position(
+                    voter_id,
+                    voter,
+                    *target,
+                    &contract_address,
+                    delegate_id,
+                );
+            }
+        }
+        for (delegate_id, votes, target) in targets.iter() {
+            if target <= votes
+                || voter.available_voting_power == 0
+                || self.voters.get(delegate_id).is_none()
+                || self.is_delegator(delegate_id)
+                || !self.delegate_accepts(
+                    delegate_id,
+                    &self.internal_get_delegation_scope(voter_id, delegate_id),
+                )
+            {
+                continue;
+            }
+            let target = votes + std::cmp::min(target - votes, voter.available_voting_power);
+            if *votes == 0 {
+                self.internal_create_voting_position(
+                    voter_id,
+                    voter,
+                    target,
+                    &contract_address,
+                    delegate_id,
+                );
+                MetaVoteEvent::Vote(VoteData {
+                    account_id: voter_id,
+                    contract_address: &contract_address,
+                    votable_object_id: delegate_id,
+                    voting_power: target.into(),
+                })
+                .emit();
+            } else {
+                self.internal_rebalance_voting_position(
+                    voter_id,
+                    voter,
+                    target,
+                    &contract_address,
+                    delegate_id,
+                );
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // **************
+    // * Delegation *
+    // **************
+
+    /// Delegates basis points of the caller locked voting power to several delegates.
+    /// The delegated voting power follows the caller locked voting power when it changes.
+    /// The list replaces all the caller delegations, delegations removed from the list
+    /// must wait 7 days since their last refresh. An empty list removes all delegations.
+    pub fn set_delegation(&mut self, delegations: Vec<DelegationBp>) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        require!(
+            delegations.len() <= MAX_DELEGATES,
+            format!("Cannot exceed {} delegates.", MAX_DELEGATES)
+        );
+        if !delegations.is_empty() {
+            require!(
+                self.internal_get_delegated_vp(&voter_id) == 0,
+                "Cannot delegate if you have received delegated votes."
+            );
+        }
+        let mut total_bp = 0_u32;
+        for (index, delegation) in delegations.iter().enumerate() {
+            let delegate_id = delegation.delegate_id.to_string();
+            require!(delegate_id != voter_id, "Cannot delegate to yourself.");
+            require!(
+                !delegations[..index]
+                    .iter()
+                    .any(|other| other.delegate_id == delegation.delegate_id),
+                "Duplicated delegate."
+            );
+            require!(
+                self.voters.get(&delegate_id).is_some(),
+                format!("{} is not a voter.", delegate_id)
+            );
+            require!(
+                !self.is_delegator(&delegate_id),
+                "Cannot delegate votes to someone who is also a delegator."
+            );
+            self.assert_delegate_accepts(
+                &delegate_id,
+                &self.internal_get_delegation_scope(&voter_id, &delegate_id),
+            );
+            total_bp += delegation.bp as u32;
+        }
+        require!(
+            total_bp <= 10000,
+            "Total delegations cannot exceed 10000 bp."
+        );
+
+        // remove the delegations not in the list
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        let current: Vec<VotableObjId> = voter
+            .vote_positions
+            .get(&contract_address)
+            .map(|votes_for_address| votes_for_address.keys_as_vector().to_vec())
+            .unwrap_or_default();
+        for delegate_id in current.iter() {
+            if !delegations
+                .iter()
+                .any(|delegation| delegation.delegate_id.as_str() == delegate_id)
+            {
+                self.assert_vote_can_be_removed(&voter_id, &contract_address, delegate_id, false);
+                self.internal_remove_voting_position(
+                    &voter_id,
+                    &mut voter,
+                    &contract_address,
+                    delegate_id,
+                );
+            }
+        }
+
+        if delegations.is_empty() {
+            self.delegations_bp.remove(&voter_id);
+        } else {
+            self.delegations_bp.insert(voter_id.clone(), delegations);
+            self.internal_follow_delegation(&voter_id, &mut voter);
+        }
+        self.voters.insert(&voter_id, &voter);
+    }
+
+    /// Sets when an existing delegation of the caller expires, None never expires.
+    /// After the expiry the voting power returns to the caller. The expiry cannot be
+    /// before the end of the delegation min hold, as for an undelegation.
+    pub fn set_delegation_expiry(
+        &mut self,
+        delegate_id: VoterId,
+        expires_at_ms: Option<EpochMillis>,
+    ) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        let voter = self.internal_get_voter_or_panic(&voter_id);
+        require!(
+            voter
+                .vote_positions
+                .get(&DELEGATED_CONTRACT_CODE.to_string())
+                .map(|votes_for_address| votes_for_address.get(&delegate_id).is_some())
+                .unwrap_or(false),
+            "Delegation not found."
+        );
+        self.internal_remove_delegation_expiry(&voter_id, &delegate_id);
+        if let Some(expires_at_ms) = expires_at_ms {
+            require!(
+                expires_at_ms > env::block_timestamp_ms(),
+                "The expiry must be in the future."
+            );
+            let hold_end = self.internal_delegation_hold_end(&voter_id, &delegate_id);
+            require!(
+                expires_at_ms >= hold_end,
+                format!(
+                    "The expiry cannot be before the delegation min hold ends at {}.",
+                    hold_end
+                )
+            );
+            self.internal_insert_delegation_expiry(&voter_id, &delegate_id, expires_at_ms);
+        }
+        MetaVoteEvent::DelegationExpiry(DelegationExpiryData {
+            account_id: &voter_id,
+            delegate_id: &delegate_id,
+            expires_at_ms,
+        })
+        .emit();
+    }
+
+    pub fn get_delegation_expiry(
+        &self,
+        delegator_id: VoterId,
+        delegate_id: VoterId,
+    ) -> Option<EpochMillis> {
+        self.internal_get_delegation_expiry(&delegator_id, &delegate_id)
+    }
+
+    /// Permissionless: returns the voting power of the expired delegations received by the delegates,
+    /// up to MAX_EXPIRIES_PER_CALL per delegate. Returns the expired delegations count.
+    pub fn expire_delegations(&mut self, delegate_ids: Vec<VoterId>) -> u32 {
+        delegate_ids
+            .iter()
+            .map(|delegate_id| self.internal_expire_delegations_to(delegate_id))
+            .sum()
+    }
+
+    /// Moves voting_power of the caller delegation from one delegate to another in one call,
+    /// without waiting for the min hold of "delegated" votes. The moved voting power keeps
+    /// the timestamp of the original delegation, so the hold is not reset or skipped.
+    /// A percentage delegation moves its basis points proportionally.
+    /// The scope and expiry move with the voting power: a delegation to to_delegate
+    /// must have the same ones.
+    pub fn redelegate(
+        &mut self,
+        from_delegate: VoterId,
+        to_delegate: VoterId,
+        voting_power: U128String,
+    ) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        self.internal_touch_delegations(&from_delegate);
+        self.internal_touch_delegations(&to_delegate);
+        let voting_power = voting_power.0;
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        require!(voting_power > 0, "Cannot redelegate 0 voting power.");
+        require!(
+            from_delegate != to_delegate,
+            "Cannot redelegate to the same delegate."
+        );
+        require!(
+            self.voters.get(&to_delegate).is_some(),
+            format!("{} is not a voter.", to_delegate)
+        );
+
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        let votes_for_address = voter.get_vote_position_for_address(&voter_id, &contract_address);
+        let from_votes = votes_for_address
+            .get(&from_delegate)
+            .expect("Delegation not found.");
+        let to_votes = votes_for_address.get(&to_delegate).unwrap_or(0);
+
+        // the moved voting power keeps the scope and expiry of the delegation
+        let scope = self.internal_get_delegation_scope(&voter_id, &from_delegate);
+        let expiry = self.internal_get_delegation_expiry(&voter_id, &from_delegate);
+        if to_votes > 0 {
+            require!(
+                scope == self.internal_get_delegation_scope(&voter_id, &to_delegate)
+                    && expiry == self.internal_get_delegation_expiry(&voter_id, &to_delegate),
+                format!(
+                    "The delegation to {} has a different scope or expiry.",
+                    to_delegate
+                )
+            );
+        } else {
+            let key = (voter_id.clone(), to_delegate.clone());
+            if scope == DelegationScope::All {
+                self.delegation_scopes.remove(&key);
+            } else {
+                self.delegation_scopes.insert(key, scope);
+            }
+            if let Some(expires_at) = expiry {
+                self.internal_insert_delegation_expiry(&voter_id, &to_delegate, expires_at);
+            }
+        }
+        self.validate_delegation_rules(&voter_id, &contract_address, &to_delegate);
+        require!(
+            voting_power <= from_votes,
+            format!(
+                "Not enough delegated voting power. You have {}, requested {}.",
+                from_votes, voting_power
+            )
+        );
+        self.assert_votes_not_locked(&contract_address, &from_delegate);
+
+        // the later timestamp: neither delegation min hold gets shorter
+        let from_timestamp = self.get_vote_timestamp(&voter_id, &contract_address, &from_delegate);
+        let to_timestamp = if to_votes > 0 {
+            std::cmp::max(
+                from_timestamp,
+                self.get_vote_timestamp(&voter_id, &contract_address, &to_delegate),
+            )
+        } else {
+            from_timestamp
+        };
+
+        self.internal_move_delegation_bp(
+            &voter_id,
+            &from_delegate,
+            &to_delegate,
+            voting_power,
+            from_votes,
+        );
+
+        // decrease first, to free the voting power
+        if voting_power == from_votes {
+            self.internal_remove_voting_position(
+                &voter_id,
+                &mut voter,
+                &contract_address,
+                &from_delegate,
+            );
+        } else {
+            self.internal_rebalance_voting_position(
+                &voter_id,
+                &mut voter,
+                from_votes - voting_power,
+                &contract_address,
+                &from_delegate,
+            );
+            self.restore_vote_timestamp(
+                &voter_id,
+                &contract_address,
+                &from_delegate,
+                from_timestamp,
+            );
+        }
+        if to_votes == 0 {
+            self.internal_create_voting_position(
+                &voter_id,
+                &mut voter,
+                voting_power,
+                &contract_address,
+                &to_delegate,
+            );
+            MetaVoteEvent::Vote(VoteData {
+                account_id: &voter_id,
+                contract_address: &contract_address,
+                votable_object_id: &to_delegate,
+                voting_power: voting_power.into(),
+            })
+            .emit();
+        } else {
+            self.internal_rebalance_voting_position(
+                &voter_id,
+                &mut voter,
+                to_votes + voting_power,
+                &contract_address,
+                &to_delegate,
+            );
+        }
+        self.restore_vote_timestamp(&voter_id, &contract_address, &to_delegate, to_timestamp);
+        self.voters.insert(&voter_id, &voter);
+
+        MetaVoteEvent::Redelegate(RedelegateData {
+            account_id: &voter_id,
+            from_delegate: &from_delegate,
+            to_delegate: &to_delegate,
+            voting_power: voting_power.into(),
+        })
+        .emit();
+    }
+
+    pub fn get_delegation(&self, voter_id: VoterId) -> Vec<DelegationBp> {
+        self.delegations_bp
+            .get(&voter_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
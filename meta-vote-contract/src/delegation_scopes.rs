@@ -0,0 +1,188 @@
+use crate::delegate_registry::assert_valid_delegation_scope;
+use crate::*;
+use near_sdk::json_types::U128;
+
+impl MetaVoteContract {
+    /// the scope of a delegation, DelegationScope::All if not set
+    pub(crate) fn internal_get_delegation_scope(
+        &self,
+        delegator_id: &str,
+        delegate_id: &str,
+    ) -> DelegationScope {
+        self.delegation_scopes
+            .get(&(delegator_id.to_string(), delegate_id.to_string()))
+            .cloned()
+            .unwrap_or(DelegationScope::All)
+    }
+
+    /// voting power of the voter unrestricted pool (locked + All delegations) used by votes.
+    /// Votes in an app draw first from the delegations scoped to the app.
+    fn internal_base_pool(&self, voter_id: &String, voter: &Voter) -> (u128, u128) {
+        let base = voter.sum_locked_vp()
+            + self
+                .internal_get_delegated_vp(voter_id)
+                .saturating_sub(voter.sum_scoped_delegated_vp());
+        let mut base_used = 0;
+        for contract_address in voter.vote_positions.keys_as_vector().iter() {
+            let used: u128 = voter
+                .vote_positions
+                .get(&contract_address)
+                .unwrap()
+                .values()
+                .sum();
+            let scoped = voter.get_scoped_delegated_vp(&DelegationScope::App { contract_address });
+            base_used += used.saturating_sub(scoped);
+        }
+        (base, base_used)
+    }
+
+    /// how much voting power the voter can add to votes in the app
+    pub(crate) fn internal_vote_pool_room(
+        &self,
+        voter_id: &String,
+        voter: &Voter,
+        contract_address: &ContractAddress,
+    ) -> u128 {
+        if voter.scoped_delegated_vp.is_empty() {
+            return voter.available_voting_power;
+        }
+        let (base, base_used) = self.internal_base_pool(voter_id, voter);
+        let scoped = voter.get_scoped_delegated_vp(&DelegationScope::App {
+            contract_address: contract_address.clone(),
+        });
+        let used: u128 = voter
+            .vote_positions
+            .get(contract_address)
+            .map(|votes_for_address| votes_for_address.values().sum())
+            .unwrap_or(0);
+        std::cmp::min(
+            voter.available_voting_power,
+            base.saturating_sub(base_used) + scoped.saturating_sub(used),
+        )
+    }
+
+    pub(crate) fn assert_vote_pool_room(
+        &self,
+        voter_id: &String,
+        voter: &Voter,
+        contract_address: &ContractAddress,
+        voting_power: u128,
+    ) {
+        let room = self.internal_vote_pool_room(voter_id, voter, contract_address);
+        require!(
+            voting_power <= room,
+            format!(
+                "Not enough voting power for this app, delegations are scoped. You have {}, requested {}.",
+                room, voting_power
+            )
+        );
+    }
+
+    /// true if the votes use more of the unrestricted pool than available,
+    /// e.g. after a scoped delegation decreased
+    pub(crate) fn is_base_pool_overdrawn(&self, voter_id: &String, voter: &Voter) -> bool {
+        if voter.scoped_delegated_vp.is_empty() {
+            return false;
+        }
+        let (base, base_used) = self.internal_base_pool(voter_id, voter);
+        base_used > base
+    }
+
+    /// voting power delegated away that counts as MPIP voting power of the delegates.
+    /// App scoped delegations are still MPIP voting power of the delegator.
+    /// Expired delegations count for the delegate until they are returned.
+    pub(crate) fn internal_mpip_delegated_away_vp(&self, voter_id: &str, voter: &Voter) -> u128 {
+        voter
+            .vote_positions
+            .get(&DELEGATED_CONTRACT_CODE.to_string())
+            .map(|votes_for_address| {
+                votes_for_address
+                    .iter()
+                    .filter(|(delegate_id, _)| {
+                        !matches!(
+                            self.internal_get_delegation_scope(voter_id, delegate_id),
+                            DelegationScope::App { .. }
+                        )
+                    })
+                    .map(|(_, votes)| votes)
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// Sets what the delegate can do with the caller existing delegation to them:
+    /// All (default), Mpip (only MPIPs) or App (only votes in the app, no MPIPs).
+    /// Applies to the current delegation and to the next ones to the same delegate.
+    /// Moving the delegation out of the delegate pool is a removal: the min hold applies.
+    pub fn set_delegation_scope(&mut self, delegate_id: VoterId, scope: DelegationScope) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        assert_valid_delegation_scope(&scope);
+        if let DelegationScope::App { contract_address } = &scope {
+            require!(
+                contract_address != DELEGATED_CONTRACT_CODE,
+                "Reserved contract address."
+            );
+        }
+        let contract_address = DELEGATED_CONTRACT_CODE.to_string();
+        let voter = self.internal_get_voter_or_panic(&voter_id);
+        let delegated = voter
+            .vote_positions
+            .get(&contract_address)
+            .and_then(|votes_for_address| votes_for_address.get(&delegate_id))
+            .unwrap_or(0);
+        require!(delegated > 0, "Delegation not found.");
+        self.assert_delegate_accepts(&delegate_id, &scope);
+        let old_scope = self.internal_get_delegation_scope(&voter_id, &delegate_id);
+        if old_scope == scope {
+            return;
+        }
+        self.assert_vote_can_be_removed(&voter_id, &contract_address, &delegate_id, false);
+        let key = (voter_id.clone(), delegate_id.clone());
+        if scope == DelegationScope::All {
+            self.delegation_scopes.remove(&key);
+        } else {
+            self.delegation_scopes.insert(key, scope.clone());
+        }
+
+        // move the current delegation between the delegate pools
+        self.internal_seed_vp_checkpoints(&delegate_id);
+        if let Some(mut delegate) = self.voters.get(&delegate_id) {
+            delegate.remove_scoped_delegated_vp(&old_scope, delegated);
+            delegate.add_scoped_delegated_vp(&scope, delegated);
+            self.adjust_voter_voting_power(&delegate_id, &mut delegate);
+            self.internal_after_vp_change(&delegate_id, &mut delegate);
+            self.voters.insert(&delegate_id, &delegate);
+        }
+        self.write_vp_checkpoints(&voter_id, &voter);
+        MetaVoteEvent::UpdateDelegationScope(DelegationScopeData {
+            account_id: &voter_id,
+            delegate_id: &delegate_id,
+            scope: &scope,
+        })
+        .emit();
+    }
+
+    pub fn get_delegation_scope(
+        &self,
+        delegator_id: VoterId,
+        delegate_id: VoterId,
+    ) -> DelegationScope {
+        self.internal_get_delegation_scope(&delegator_id, &delegate_id)
+    }
+
+    /// delegate: received delegated voting power restricted to a scope
+    pub fn get_scoped_delegated_voting_power(
+        &self,
+        voter_id: VoterId,
+    ) -> Vec<(DelegationScope, U128)> {
+        self.internal_get_voter(&voter_id)
+            .scoped_delegated_vp
+            .into_iter()
+            .map(|(scope, voting_power)| (scope, voting_power.into()))
+            .collect()
+    }
+}
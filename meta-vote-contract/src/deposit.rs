@@ -1,4 +1,5 @@
 use crate::buy_and_lock::{ReceiveTokenOptions, TokenAndAmount};
+use crate::merkle_claims::MerkleClaimsInfo;
 use crate::*;
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
@@ -47,6 +48,14 @@ impl FungibleTokenReceiver for MetaVoteContract {
                 Err(_) => panic!("Err parsing msg for-claims"),
             };
         }
+        // if msg == "for-merkle-claims:{merkle_root:x,bpu:x}"
+        // tokens to be claimed later by each voter with a merkle proof
+        else if msg.len() >= 18 && &msg[..18] == "for-merkle-claims:" {
+            match serde_json::from_str::<MerkleClaimsInfo>(&msg[18..]) {
+                Ok(info) => self.create_merkle_distribution(sender_id, amount, info),
+                Err(_) => panic!("Err parsing msg for-merkle-claims"),
+            };
+        }
         // if we're receiving mpDAO
         // then it is a deposit & lock [& vote] (for sender or others)
         else if env::predecessor_account_id() == self.mpdao_token_contract_address {
@@ -87,6 +96,7 @@ impl FungibleTokenReceiver for MetaVoteContract {
                     }
                 };
                 // lock mpDAO for signer or others
+                self.internal_seed_vp_checkpoints(&voter_id);
                 let mut voter = self.internal_get_voter(&voter_id);
                 self.deposit_locking_position(amount, days, &voter_id, &mut voter);
             }
@@ -124,26 +134,21 @@ impl MetaVoteContract {
             for item in &distribute_info.data {
                 // in case of mpDAO, item.1 is integer mpDAO - mpDAO has 6 decimals
                 let total_mpdao_amount = item.1 as u128 * 1_000_000;
-                let unlocked_amount = apply_bp(total_mpdao_amount, distribute_info.bpu);
-                let locked_amount = total_mpdao_amount - unlocked_amount;
-                if unlocked_amount > 0 {
-                    // portion to be distributed as unlocked
-                    let existing = self
-                        .claimable_unlocked_mpdao
-                        .get(&item.0)
-                        .unwrap_or_default();
-                    self.claimable_unlocked_mpdao
-                        .insert(&item.0, &(existing + unlocked_amount));
-                    self.total_unclaimed_unlocked_mpdao += unlocked_amount;
-                    self.accumulated_unlocked_mpdao_distributed_for_claims += unlocked_amount;
-                };
-                if locked_amount > 0 {
-                    // add locking claim
-                    self.add_claimable_mpdao(&item.0, locked_amount);
-                }
+                self.add_claimable_mpdao_with_bpu(&item.0, total_mpdao_amount, distribute_info.bpu);
                 total_distributed += total_mpdao_amount;
             }
             self.accumulated_mpdao_distributed_for_claims += total_distributed;
+            // early exit penalties in the pool are added to this distribution
+            let from_penalty_pool = total_distributed.saturating_sub(total_amount);
+            assert!(
+                from_penalty_pool <= self.early_exit_penalty_pool,
+                "total to distribute {} > total_amount sent {} + early exit penalty pool {}",
+                total_distributed,
+                total_amount,
+                self.early_exit_penalty_pool
+            );
+            self.early_exit_penalty_pool -= from_penalty_pool;
+            total_distributed -= from_penalty_pool;
 
         // stNear Token
         } else if token_address == self.stnear_token_contract_address {
@@ -167,5 +172,12 @@ impl MetaVoteContract {
             total_distributed,
             total_amount
         );
+        MetaVoteEvent::DistributeForClaims(DistributionData {
+            distribution_id: None,
+            token_contract: token_address.as_str(),
+            total_amount: total_amount.into(),
+            bpu: distribute_info.bpu,
+        })
+        .emit();
     }
 }
@@ -0,0 +1,196 @@
+use crate::*;
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{ext_contract, PromiseResult};
+
+/// where the early exit penalties go
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PenaltyDestination {
+    Burn,                               // ft_burn on the mpDAO token
+    Treasury { account_id: AccountId }, // ft_transfer to the treasury
+    ClaimsPool,                         // added to the next mpDAO distribute_for_claims
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarlyExitConfig {
+    /// penalty for a position with max_unbond_period days remaining, in basis points.
+    /// The penalty scales linearly with the remaining unbond time.
+    pub max_penalty_bp: u16,
+    pub penalty_destination: PenaltyDestination,
+}
+
+#[allow(dead_code)]
+#[ext_contract(ext_mpdao_token)]
+pub trait MpdaoToken {
+    fn ft_burn(&mut self, amount: U128, memo: Option<String>);
+}
+
+impl MetaVoteContract {
+    /// penalty for exiting now, scaled by the remaining unbond time
+    fn internal_early_exit_penalty(
+        &self,
+        config: &EarlyExitConfig,
+        locking_position: &LockingPosition,
+    ) -> MpDAOAmount {
+        let remaining_millis = match locking_position.unlocking_started_at {
+            None => locking_position.locking_period_millis(),
+            Some(date) => (date + locking_position.locking_period_millis())
+                .saturating_sub(get_current_epoch_millis()),
+        };
+        let max_millis = days_to_millis(self.max_unbond_period);
+        proportional(
+            apply_bp(locking_position.amount, config.max_penalty_bp),
+            std::cmp::min(remaining_millis, max_millis).into(),
+            max_millis.into(),
+        )
+    }
+
+    fn send_early_exit_penalty(&mut self, penalty: MpDAOAmount, destination: &PenaltyDestination) {
+        if penalty == 0 {
+            return;
+        }
+        let callback = || {
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                .after_early_exit_penalty_callback(U128::from(penalty))
+        };
+        match destination {
+            PenaltyDestination::ClaimsPool => self.early_exit_penalty_pool += penalty,
+            PenaltyDestination::Burn => {
+                ext_mpdao_token::ext(self.mpdao_token_contract_address.clone())
+                    .with_static_gas(GAS_FOR_FT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .ft_burn(U128::from(penalty), Some("early exit penalty".into()))
+                    .then(callback());
+            }
+            PenaltyDestination::Treasury { account_id } => {
+                ext_ft_core::ext(self.mpdao_token_contract_address.clone())
+                    .with_static_gas(GAS_FOR_FT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .ft_transfer(
+                        account_id.clone(),
+                        U128::from(penalty),
+                        Some("early exit penalty".into()),
+                    )
+                    .then(callback());
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // **************
+    // * Early exit *
+    // **************
+
+    /// Removes a locked or unlocking position and returns the mpDAO now,
+    /// minus a penalty that scales with the remaining unbond time.
+    /// Votes must be removed first if the remaining voting power is not enough.
+    #[payable]
+    pub fn early_exit_position(&mut self, position_id: PositionId) {
+        assert_one_yocto();
+        let config = self
+            .early_exit_config
+            .clone()
+            .expect("Early exit is disabled");
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
+        let locking_position = voter.get_position(index);
+        self.assert_not_nft_position(&locking_position);
+        require!(
+            !locking_position.is_unlocked(),
+            "The position is already unlocked, use withdraw"
+        );
+        let penalty = self.internal_early_exit_penalty(&config, &locking_position);
+
+        voter.remove_position(index);
+        // make sure there was enough available voting power
+        self.require_vp_available(&voter_id, &mut voter);
+        let voting_power_removed = if locking_position.is_locked() {
+            locking_position.voting_power
+        } else {
+            0 // unlocking positions vp is not part of total_voting_power
+        };
+        self.total_voting_power = self.total_voting_power.saturating_sub(voting_power_removed);
+
+        MetaVoteEvent::EarlyExit(EarlyExitData {
+            account_id: &voter_id,
+            position_id,
+            amount: locking_position.amount.into(),
+            penalty: penalty.into(),
+            voting_power: voting_power_removed.into(),
+        })
+        .emit();
+
+        self.internal_after_vp_change(&voter_id, &mut voter);
+        if voter.is_empty() {
+            self.voters.remove(&voter_id);
+            self.vote_allocations_bp.remove(&voter_id);
+            self.delegations_bp.remove(&voter_id);
+            log!("GODSPEED: {} is no longer part of Meta Vote!", &voter_id);
+        } else {
+            self.voters.insert(&voter_id, &voter);
+        }
+        self.transfer_mpdao_to_voter(
+            env::predecessor_account_id(),
+            locking_position.amount - penalty,
+        );
+        self.send_early_exit_penalty(penalty, &config.penalty_destination);
+    }
+
+    /// a failed burn or treasury transfer leaves the penalty in the claims pool
+    #[private]
+    pub fn after_early_exit_penalty_callback(&mut self, amount: U128) {
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => self.early_exit_penalty_pool += amount.0,
+        };
+    }
+
+    /// None disables early exits
+    #[payable]
+    pub fn set_early_exit_config(&mut self, config: Option<EarlyExitConfig>) {
+        assert_one_yocto();
+        self.assert_owner_or_mpip();
+        if let Some(config) = &config {
+            require!(
+                config.max_penalty_bp <= 10000,
+                "max_penalty_bp must be between 0 and 10000"
+            );
+        }
+        emit_config_change(
+            "early_exit_config",
+            near_sdk::serde_json::to_string(&config).expect("config serialization"),
+        );
+        self.early_exit_config = config;
+    }
+
+    pub fn get_early_exit_config(&self) -> Option<EarlyExitConfig> {
+        self.early_exit_config.clone()
+    }
+
+    /// mpDAO from early exit penalties waiting for the next mpDAO distribution
+    pub fn get_early_exit_penalty_pool(&self) -> U128String {
+        self.early_exit_penalty_pool.into()
+    }
+
+    /// the penalty if the position exits now
+    pub fn get_early_exit_penalty(&self, voter_id: VoterId, position_id: PositionId) -> U128String {
+        let config = self
+            .early_exit_config
+            .as_ref()
+            .expect("Early exit is disabled");
+        let voter = self.internal_get_voter_or_panic(&voter_id);
+        let locking_position = voter.get_position(voter.get_position_index(position_id));
+        self.internal_early_exit_penalty(config, &locking_position)
+            .into()
+    }
+}
@@ -0,0 +1,312 @@
+use crate::*;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json;
+
+// NEP-297 events: EVENT_JSON:{"standard":"meta-vote","version":"2.0.0","event":"...","data":{...}}
+// bump EVENT_STANDARD_VERSION when a data struct changes
+pub const EVENT_STANDARD: &str = "meta-vote";
+pub const EVENT_STANDARD_VERSION: &str = "2.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum MetaVoteEvent<'a> {
+    Lock(LockData<'a>),
+    Unlock(UnlockData<'a>),
+    Relock(RelockData<'a>),
+    Extend(ExtendData<'a>),
+    Merge(MergeData<'a>),
+    Split(SplitData<'a>),
+    Withdraw(AmountData<'a>),
+    WithdrawFailed(AmountData<'a>), // mpDAO returned to the voter balance
+    EarlyExit(EarlyExitData<'a>),
+    Vote(VoteData<'a>),
+    Unvote(VoteData<'a>),
+    Rebalance(RebalanceData<'a>),
+    StaleVoteRemoved(StaleVoteData<'a>),
+    EvmPreDelegate(EvmDelegationData<'a>),
+    EvmPreDelegateRemoved(EvmDelegationData<'a>),
+    EvmDelegate(EvmDelegationData<'a>),
+    EvmUndelegate(EvmDelegationData<'a>),
+    DistributeForClaims(DistributionData<'a>),
+    MerkleDistribution(DistributionData<'a>),
+    Claim(ClaimData<'a>),
+    MerkleClaim(MerkleClaimData<'a>),
+    TransferFailed(ClaimData<'a>), // claim returned to the account claimable
+    BuyAndLock(BuyAndLockData<'a>),
+    UpdatePrice(PriceData<'a>),
+    DeleteAllPrices,
+    UpdateConfig(ConfigData<'a>),
+    RegisterApp(AppData<'a>),
+    UpdateApp(AppData<'a>),
+    AddAppObjects(AppObjectsData<'a>),
+    RemoveAppObjects(AppObjectsData<'a>),
+    CreateRound(RoundData<'a>),
+    FinalizeRound(RoundData<'a>),
+    DelegationExpiry(DelegationExpiryData<'a>),
+    DelegationExpired(DelegationData<'a>), // follows the unvote of the expired delegation
+    RegisterDelegate(DelegateData<'a>),
+    UpdateDelegate(DelegateData<'a>),
+    UpdateDelegationScope(DelegationScopeData<'a>),
+    Redelegate(RedelegateData<'a>), // follows the unvote/rebalance and vote/rebalance of the move
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockData<'a> {
+    pub account_id: &'a str,
+    pub amount: U128,
+    pub locking_period: Days,
+    pub voting_power: U128, // vp added
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnlockData<'a> {
+    pub account_id: &'a str,
+    pub position_id: PositionId,
+    pub amount: U128,
+    pub voting_power: U128, // vp removed
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RelockData<'a> {
+    pub account_id: &'a str,
+    pub position_id: Option<PositionId>, // None when relocking from balance
+    pub amount_from_position: U128,
+    pub amount_from_balance: U128,
+    pub locking_period: Days,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExtendData<'a> {
+    pub account_id: &'a str,
+    pub position_id: PositionId,
+    pub locking_period: Days,
+    pub voting_power: U128, // new position vp
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarlyExitData<'a> {
+    pub account_id: &'a str,
+    pub position_id: PositionId,
+    pub amount: U128,
+    pub penalty: U128,
+    pub voting_power: U128, // vp removed, 0 for unlocking positions
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MergeData<'a> {
+    pub account_id: &'a str,
+    pub position_ids: &'a [PositionId], // merged positions
+    pub position_id: PositionId,        // resulting position
+    pub locking_period: Days,
+    pub voting_power: U128, // resulting position vp
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SplitData<'a> {
+    pub account_id: &'a str,
+    pub position_id: PositionId,
+    pub new_position_id: PositionId, // position that received the amount
+    pub amount: U128,
+    pub locking_period: Days,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AmountData<'a> {
+    pub account_id: &'a str,
+    pub amount: U128,
+}
+
+/// contract_address is "delegated" and votable_object_id the delegate for vp delegations
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VoteData<'a> {
+    pub account_id: &'a str,
+    pub contract_address: &'a str,
+    pub votable_object_id: &'a str,
+    pub voting_power: U128, // vp added (vote) or removed (unvote)
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RebalanceData<'a> {
+    pub account_id: &'a str,
+    pub contract_address: &'a str,
+    pub votable_object_id: &'a str,
+    pub previous_voting_power: U128,
+    pub voting_power: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StaleVoteData<'a> {
+    pub account_id: &'a str,
+    pub contract_address: &'a str,
+    pub votable_object_id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EvmDelegationData<'a> {
+    pub evm_address: &'a str,
+    pub account_id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionData<'a> {
+    pub distribution_id: Option<u32>, // only for merkle distributions
+    pub token_contract: &'a str,
+    pub total_amount: U128,
+    pub bpu: u16,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimData<'a> {
+    pub account_id: &'a str,
+    pub receiver_id: &'a str,
+    pub token_contract: &'a str,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MerkleClaimData<'a> {
+    pub distribution_id: u32,
+    pub index: u64,
+    pub account_id: &'a str,
+    pub token_contract: &'a str,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BuyAndLockData<'a> {
+    pub account_id: &'a str,
+    pub beneficiary_id: &'a str,
+    pub token_contract: &'a str,
+    pub token_amount: U128,
+    pub mpdao_amount: U128,
+    pub locking_period: Days,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceData<'a> {
+    pub token_contract: &'a str,
+    pub mpdao_per_token_e9: U64,
+    pub paused: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigData<'a> {
+    pub key: &'a str,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AppData<'a> {
+    pub contract_address: &'a str,
+    pub owner_id: &'a str,
+    pub archived: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AppObjectsData<'a> {
+    pub contract_address: &'a str,
+    pub votable_object_ids: &'a [VotableObjId],
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegationData<'a> {
+    pub account_id: &'a str,
+    pub delegate_id: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegationExpiryData<'a> {
+    pub account_id: &'a str,
+    pub delegate_id: &'a str,
+    pub expires_at_ms: Option<EpochMillis>, // None never expires
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegationScopeData<'a> {
+    pub account_id: &'a str,
+    pub delegate_id: &'a str,
+    pub scope: &'a DelegationScope,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RedelegateData<'a> {
+    pub account_id: &'a str,
+    pub from_delegate: &'a str,
+    pub to_delegate: &'a str,
+    pub voting_power: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegateData<'a> {
+    pub account_id: &'a str,
+    pub status: &'a DelegateStatus,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoundData<'a> {
+    pub round_id: u32,
+    pub contract_address: &'a str,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub freeze_ms: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a MetaVoteEvent<'a>,
+}
+
+impl MetaVoteEvent<'_> {
+    pub fn emit(&self) {
+        let event_log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        };
+        log!(
+            "EVENT_JSON:{}",
+            serde_json::to_string(&event_log).expect("event serialization")
+        );
+    }
+}
+
+/// owner/operator config changes
+pub fn emit_config_change(key: &str, value: impl ToString) {
+    MetaVoteEvent::UpdateConfig(ConfigData {
+        key,
+        value: value.to_string(),
+    })
+    .emit();
+}
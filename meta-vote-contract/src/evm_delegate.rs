@@ -1,6 +1,39 @@
 use crate::*;
 use near_sdk::{assert_one_yocto, near_bindgen, PromiseOrValue};
 
+impl MetaVoteContract {
+    fn internal_get_evm_delegation_nonce(&self, evm_address: &EvmAddress) -> u64 {
+        self.evm_delegation_nonces
+            .get(evm_address)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// the message to sign to delegate evm_address to account_id, bound to this contract
+    /// and to the evm_address nonce, so a signature cannot be replayed after a new delegation
+    fn evm_delegation_message(&self, evm_address: &EvmAddress, account_id: &str) -> String {
+        format!(
+            "delegate to {} on {} nonce {}",
+            account_id,
+            env::current_account_id(),
+            self.internal_get_evm_delegation_nonce(evm_address)
+        )
+    }
+
+    /// the key evm_address is stored with: normalized, or as given for delegations
+    /// stored before the addresses were normalized
+    fn internal_evm_delegation_key(&self, evm_address: &str) -> Option<EvmAddress> {
+        let normalized = utils::normalize_evm_address(evm_address);
+        if self.evm_delegation_signatures.get(&normalized).is_some() {
+            Some(normalized)
+        } else if self.evm_delegation_signatures.get(evm_address).is_some() {
+            Some(evm_address.to_string())
+        } else {
+            None
+        }
+    }
+}
+
 #[near_bindgen]
 impl MetaVoteContract {
     // ************************
@@ -9,7 +42,10 @@ impl MetaVoteContract {
 
     #[payable]
     /// called from the user account, with a ECDSA signature (ethereum signatures & the evm account)
-    /// if confirmed by the operator, it moves to "delegated"
+    /// of the message "delegate to {account_id} on {contract_id} nonce {nonce}",
+    /// see get_evm_delegation_message.
+    /// If the signature is verified on-chain it moves directly to "delegated",
+    /// else it is kept as pre-delegation and if confirmed by the operator, it moves to "delegated"
     pub fn pre_delegate_evm_address(&mut self, evm_address: String, signature: String) {
         assert_one_yocto();
         // minimal checks to avoid common mistakes (e.g. send with .evmp.near)
@@ -17,60 +53,108 @@ impl MetaVoteContract {
             !evm_address.contains("."),
             "evm_address can not contain dots"
         );
-        let account_id = env::predecessor_account_id();
-        self.evm_pre_delegation
-            .insert(evm_address, (account_id.into(), signature));
+        let evm_address = utils::normalize_evm_address(&evm_address);
+        let account_id: String = env::predecessor_account_id().into();
+        let message = self.evm_delegation_message(&evm_address, &account_id);
+        if utils::recover_evm_address(&message, &signature).as_ref() == Some(&evm_address) {
+            self.evm_pre_delegation.remove(&evm_address);
+            self.internal_confirm_evm_delegation(evm_address, account_id, signature);
+        } else {
+            // can not verify on-chain (e.g. smart-contract wallet), wait for the operator
+            MetaVoteEvent::EvmPreDelegate(EvmDelegationData {
+                evm_address: &evm_address,
+                account_id: &account_id,
+            })
+            .emit();
+            self.evm_pre_delegation
+                .insert(evm_address, (account_id, signature));
+        }
+    }
+
+    /// the message the evm_address owner signs to delegate to account_id
+    pub fn get_evm_delegation_message(&self, evm_address: String, account_id: AccountId) -> String {
+        self.evm_delegation_message(
+            &utils::normalize_evm_address(&evm_address),
+            account_id.as_str(),
+        )
     }
 
     pub fn get_pre_delegate_evm_address(&self, evm_address: String) -> Option<&(String, String)> {
-        self.evm_pre_delegation.get(&evm_address)
+        self.evm_pre_delegation
+            .get(&utils::normalize_evm_address(&evm_address))
     }
 
     #[payable]
     pub fn operator_remove_pre_delegate_evm_address(&mut self, evm_address: String) {
         assert_one_yocto();
         self.assert_operator();
-        self.evm_pre_delegation.remove(&evm_address);
+        let evm_address = utils::normalize_evm_address(&evm_address);
+        if let Some(pre_delegation) = self.evm_pre_delegation.remove(&evm_address) {
+            MetaVoteEvent::EvmPreDelegateRemoved(EvmDelegationData {
+                evm_address: &evm_address,
+                account_id: &pre_delegation.0,
+            })
+            .emit();
+        }
     }
 
+    /// the operator verifies off-chain the signature of get_evm_delegation_message
     #[payable]
     pub fn operator_confirm_delegated_evm_address(&mut self, evm_address: String) {
         assert_one_yocto();
         self.assert_operator();
+        let evm_address = utils::normalize_evm_address(&evm_address);
         if let Some(pre_delegation) = self.evm_pre_delegation.remove(&evm_address) {
-            let account_id = pre_delegation.0;
-            let evm_signature = pre_delegation.1;
-            if let Some(existing_delegation) = self.evm_delegation_signatures.get(&evm_address) {
-                // this evm_address was already delegated
-                if existing_delegation.0.eq(&account_id) {
-                    // to the the same, nothing to do
-                    // just removed the pre-delegation
-                    return;
-                } else {
-                    // it was delegated to another near address, get the list of all delegations for that address
-                    let mut previous_delegate_addresses =
-                        self.evm_delegates.get(&existing_delegation.0).unwrap();
-                    // remove evm_address from the previous account id list
-                    previous_delegate_addresses.retain(|x| !x.eq(&evm_address));
-                    // update the old delegate addresses list
-                    if previous_delegate_addresses.len() == 0 {
-                        self.evm_delegates.remove(&existing_delegation.0);
-                    } else {
-                        self.evm_delegates
-                            .insert(&existing_delegation.0, &previous_delegate_addresses);
-                    }
-                }
+            self.internal_confirm_evm_delegation(evm_address, pre_delegation.0, pre_delegation.1);
+        }
+    }
+
+    // local fn: moves evm_address (normalized) to "delegated" to account_id
+    fn internal_confirm_evm_delegation(
+        &mut self,
+        evm_address: String,
+        account_id: String,
+        evm_signature: String,
+    ) {
+        // the signature cannot be used again
+        let nonce = self.internal_get_evm_delegation_nonce(&evm_address);
+        self.evm_delegation_nonces
+            .insert(evm_address.clone(), nonce + 1);
+        // remove the current delegation, also if stored before the addresses were normalized
+        if let Some(existing_key) = self.internal_evm_delegation_key(&evm_address) {
+            let existing_delegation = self
+                .evm_delegation_signatures
+                .remove(&existing_key)
+                .unwrap();
+            // get the list of all delegations for the previous near address (can be account_id)
+            let mut previous_delegate_addresses = self
+                .evm_delegates
+                .get(&existing_delegation.0)
+                .unwrap_or_default();
+            // remove evm_address from the previous account id list
+            previous_delegate_addresses.retain(|x| !x.eq(&existing_key));
+            // update the old delegate addresses list
+            if previous_delegate_addresses.is_empty() {
+                self.evm_delegates.remove(&existing_delegation.0);
+            } else {
+                self.evm_delegates
+                    .insert(&existing_delegation.0, &previous_delegate_addresses);
             }
-            // get current delegations for account_id
-            let mut delegated_addresses = self.evm_delegates.get(&account_id).unwrap_or_default();
-            // add this one
-            delegated_addresses.push(evm_address.clone());
-            // save
-            self.evm_delegates.insert(&account_id, &delegated_addresses);
-            // also save & keep the signature
-            self.evm_delegation_signatures
-                .insert(evm_address, (account_id, evm_signature));
         }
+        // get current delegations for account_id
+        let mut delegated_addresses = self.evm_delegates.get(&account_id).unwrap_or_default();
+        // add this one
+        delegated_addresses.push(evm_address.clone());
+        // save
+        self.evm_delegates.insert(&account_id, &delegated_addresses);
+        MetaVoteEvent::EvmDelegate(EvmDelegationData {
+            evm_address: &evm_address,
+            account_id: &account_id,
+        })
+        .emit();
+        // also save & keep the signature
+        self.evm_delegation_signatures
+            .insert(evm_address, (account_id, evm_signature));
     }
 
     #[payable]
@@ -142,8 +226,11 @@ impl MetaVoteContract {
             .get(&env::predecessor_account_id().into())
             .unwrap_or_default();
         // make sure predecessor_account_id() is the delegate
+        let normalized = utils::normalize_evm_address(evm_address);
         assert!(
-            delegations.contains(&evm_address),
+            delegations
+                .iter()
+                .any(|address| utils::normalize_evm_address(address) == normalized),
             "{} is not delegated to {}",
             &evm_address,
             &env::predecessor_account_id()
@@ -177,13 +264,6 @@ impl MetaVoteContract {
     ) {
         // verify delegation and compose the pseudo near account
         let pseudo_account = self.verify_delegate(&evm_address);
-        log!(
-            "UNVOTE: delegate {} unvoted object {} at address {} as {}.",
-            &env::predecessor_account_id(),
-            &votable_object_id,
-            contract_address.as_str(),
-            pseudo_account
-        );
         self.internal_unvote(
             &pseudo_account,
             &contract_address,
@@ -195,6 +275,9 @@ impl MetaVoteContract {
     #[payable]
     pub fn remove_delegated_evm_address(&mut self, evm_address: String) {
         assert_one_yocto();
+        let evm_address = self
+            .internal_evm_delegation_key(&evm_address)
+            .unwrap_or(evm_address);
         if let Some(existing_delegation) = self.evm_delegation_signatures.get(&evm_address) {
             let predecessor = env::predecessor_account_id().as_str().to_string();
             // this evm_address is delegated
@@ -208,6 +291,11 @@ impl MetaVoteContract {
                 // save
                 self.evm_delegates
                     .insert(&predecessor, &delegated_addresses);
+                MetaVoteEvent::EvmUndelegate(EvmDelegationData {
+                    evm_address: &evm_address,
+                    account_id: &predecessor,
+                })
+                .emit();
             } else {
                 panic!("note delegated to you");
             }
@@ -251,17 +339,25 @@ impl MetaVoteContract {
 
     // return the delegate (near account) for an specific evm address or null
     pub fn get_delegate(&self, evm_address: EvmAddress) -> Option<String> {
-        if let Some(delegation) = self.evm_delegation_signatures.get(&evm_address) {
-            Some(delegation.0.to_string())
-        } else {
-            None
-        }
+        self.internal_evm_delegation_key(&evm_address)
+            .and_then(|key| self.evm_delegation_signatures.get(&key))
+            .map(|delegation| delegation.0.to_string())
     }
 
     /// returns [near_account, delegation_signature], e.g: ["alice.near”, ”xxxxxxxxxxxxxxxx”]
     /// for external verification of the validity of delegations
-    /// The message to validate against the signature is: “delegate to alice.near”
+    /// The message to validate against the signature is:
+    /// “delegate to alice.near on {contract_id} nonce {get_evm_delegation_nonce - 1}”,
+    /// or “delegate to alice.near” for delegations before the nonces
     pub fn get_delegation_signature(&self, evm_address: String) -> &(String, String) {
-        self.evm_delegation_signatures.get(&evm_address).unwrap()
+        let key = self
+            .internal_evm_delegation_key(&evm_address)
+            .expect("evm_address is not delegated");
+        self.evm_delegation_signatures.get(&key).unwrap()
+    }
+
+    /// the nonce of the next delegation message of evm_address
+    pub fn get_evm_delegation_nonce(&self, evm_address: String) -> u64 {
+        self.internal_get_evm_delegation_nonce(&utils::normalize_evm_address(&evm_address))
     }
 }
@@ -17,6 +17,14 @@ impl MetaVoteContract {
             "Only the owner can call this function."
         );
     }
+    /// the owner, or the MPIP contract executing an accepted proposal action
+    pub(crate) fn assert_owner_or_mpip(&self) {
+        let predecessor = env::predecessor_account_id();
+        require!(
+            self.owner_id == predecessor || self.mpip_contract_id.as_ref() == Some(&predecessor),
+            "Only the owner or the MPIP contract can call this function."
+        );
+    }
     pub(crate) fn assert_operator(&self) {
         require!(
             self.operator_id == env::predecessor_account_id(),
@@ -45,9 +53,11 @@ impl MetaVoteContract {
         &mut self,
         delegate_id: &String,
         voting_power: u128,
+        scope: &DelegationScope,
     ) {
         let mut delegate = self.internal_get_voter_or_panic(&delegate_id);
         delegate.available_voting_power += voting_power;
+        delegate.add_scoped_delegated_vp(scope, voting_power);
         // save delegate
         self.voters.insert(&delegate_id, &delegate);
     }
@@ -56,11 +66,13 @@ impl MetaVoteContract {
         &mut self,
         delegate_id: &String,
         voting_power: u128,
+        scope: &DelegationScope,
     ) {
         let delegate = self.voters.get(&delegate_id);
         if let Some(mut delegate) = delegate {
             delegate.available_voting_power =
                 delegate.available_voting_power.saturating_sub(voting_power);
+            delegate.remove_scoped_delegated_vp(scope, voting_power);
             // save delegate
             self.voters.insert(&delegate_id, &delegate);
         }
@@ -95,7 +107,9 @@ impl MetaVoteContract {
 
     /// call this after reducing locking positions to ensure enough free voting power
     /// existed before the change
-    pub(crate) fn require_vp_available(&self, voter_id: &String, voter: &mut Voter) {
+    /// percentage delegations & allocations are reduced first
+    pub(crate) fn require_vp_available(&mut self, voter_id: &String, voter: &mut Voter) {
+        self.internal_follow_vp_shares(voter_id, voter);
         self.internal_common_update_available(true, voter_id, voter);
     }
 
@@ -135,15 +149,27 @@ impl MetaVoteContract {
                     &vote_pos.votable_address,
                     &vote_pos.votable_object_id,
                 );
-                log!(
-                    "Removed vote for {} / {} of {} vp",
-                    vote_pos.votable_address,
-                    vote_pos.votable_object_id,
-                    vote_pos.voting_power
-                );
                 used_voting_power -= vote_pos.voting_power;
             }
         }
+        // votes cannot use more than the unrestricted voting power, see internal_vote_pool_room
+        if self.is_base_pool_overdrawn(voter_id, voter) {
+            let mut vote_positions_by_power = Vec::new();
+            for address in voter.vote_positions.keys_as_vector().iter() {
+                let pos = voter.vote_positions.get(&address).unwrap();
+                for (obj, voting_power) in pos.iter() {
+                    vote_positions_by_power.push((voting_power, address.clone(), obj));
+                }
+            }
+            vote_positions_by_power.sort();
+            for (voting_power, address, obj) in vote_positions_by_power.iter() {
+                if !self.is_base_pool_overdrawn(voter_id, voter) {
+                    break;
+                }
+                self.internal_remove_voting_position(voter_id, voter, address, obj);
+                used_voting_power -= voting_power;
+            }
+        }
         voter.available_voting_power = new_voting_power - used_voting_power;
     }
 
@@ -181,6 +207,9 @@ impl MetaVoteContract {
 
         votes_for_address.insert(&votable_object_id, &votes);
         self.votes.insert(&contract_address, &votes_for_address);
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_update_delegate_rank(votable_object_id, votes - voting_power, votes);
+        }
     }
 
     pub(crate) fn state_internal_decrease_total_votes_for_address(
@@ -207,6 +236,9 @@ impl MetaVoteContract {
         } else {
             self.votes.insert(&contract_address, &votes_for_address);
         }
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_update_delegate_rank(votable_object_id, votes + voting_power, votes);
+        }
     }
 
     // ***************************
@@ -279,6 +311,27 @@ impl MetaVoteContract {
         );
     }
 
+    /// splits a mpDAO distribution between locked and unlocked claims
+    /// bpu = basis points unlocked (0-10000)
+    pub(crate) fn add_claimable_mpdao_with_bpu(
+        &mut self,
+        account: &String,
+        total_mpdao_amount: u128,
+        bpu: u16,
+    ) {
+        let unlocked_amount = apply_bp(total_mpdao_amount, bpu);
+        let locked_amount = total_mpdao_amount - unlocked_amount;
+        if unlocked_amount > 0 {
+            // portion to be distributed as unlocked
+            self.add_claimable_unlocked_mpdao(account, unlocked_amount);
+            self.accumulated_unlocked_mpdao_distributed_for_claims += unlocked_amount;
+        };
+        if locked_amount > 0 {
+            // add locking claim
+            self.add_claimable_mpdao(account, locked_amount);
+        }
+    }
+
     pub(crate) fn remove_claimable_mpdao(&mut self, account: &String, amount: u128) {
         Self::remove_claimable(
             &mut self.claimable_mpdao,
@@ -317,6 +370,13 @@ impl MetaVoteContract {
     ) -> Promise {
         // remove claim
         self.remove_claimable_stnear(&voter_id, amount);
+        MetaVoteEvent::Claim(ClaimData {
+            account_id: voter_id,
+            receiver_id: receiver_id.as_str(),
+            token_contract: self.stnear_token_contract_address.as_str(),
+            amount: amount.into(),
+        })
+        .emit();
         // transfer to destination
         self.transfer_claimable_stnear_to_receiver(&voter_id, receiver_id, amount)
     }
@@ -335,7 +395,15 @@ impl MetaVoteContract {
         );
         self.assert_min_deposit_amount(amount);
         self.remove_claimable_mpdao(&account, amount);
+        MetaVoteEvent::Claim(ClaimData {
+            account_id: account,
+            receiver_id: beneficiary_id,
+            token_contract: self.mpdao_token_contract_address.as_str(),
+            amount: amount.into(),
+        })
+        .emit();
         // get beneficiary voter
+        self.internal_seed_vp_checkpoints(beneficiary_id);
         let mut beneficiary_voter = self.internal_get_voter(&beneficiary_id);
         // create/update locking position
         self.deposit_locking_position(
@@ -355,10 +423,18 @@ impl MetaVoteContract {
     ) -> PromiseOrValue<u128> {
         // remove claim from unlocked bucket
         self.remove_claimable_unlocked_mpdao(voter_id, amount);
+        MetaVoteEvent::Claim(ClaimData {
+            account_id: voter_id,
+            receiver_id: receiver_id.as_str(),
+            token_contract: self.mpdao_token_contract_address.as_str(),
+            amount: amount.into(),
+        })
+        .emit();
         let unbond_days = optional_unbond_days.unwrap_or(0);
         if unbond_days > 0 {
             self.assert_min_deposit_amount(amount);
             // get beneficiary voter
+            self.internal_seed_vp_checkpoints(voter_id);
             let mut beneficiary_voter = self.internal_get_voter(&voter_id);
             // create/update locking position
             self.deposit_locking_position(amount, unbond_days, &voter_id, &mut beneficiary_voter);
@@ -1,14 +1,27 @@
 use crate::{
+    apps::VotableApp,
     buy_and_lock::{MpdaoPrice, TokenInfo},
+    checkpoints::VpCheckpoint,
     constants::*,
+    delegate_registry::*,
+    delegation::DelegationBp,
+    early_exit::EarlyExitConfig,
+    events::*,
     internal::DELEGATED_CONTRACT_CODE,
     locking_position::*,
+    merkle_claims::MerkleDistribution,
+    nft::NftPosition,
+    rounds::{RoundId, VotingRound},
+    stale_policy::*,
     utils::*,
+    vote_allocations::VoteAllocationBp,
+    vp_curve::*,
 };
+use near_contract_standards::non_fungible_token::TokenId;
 use near_sdk::{
     assert_one_yocto,
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{unordered_map::UnorderedMap, Vector},
+    collections::{unordered_map::UnorderedMap, TreeMap, UnorderedSet, Vector},
     env, log, near_bindgen, require,
     store::LookupMap,
     AccountId, Balance, CryptoHash, PanicOnDefault, Promise, PromiseOrValue, ONE_NEAR,
@@ -16,18 +29,33 @@ use near_sdk::{
 use types::*;
 use voter::Voter;
 
+mod apps;
 mod buy_and_lock;
+mod checkpoints;
 mod constants;
+mod delegate_registry;
+mod delegation;
+mod delegation_scopes;
 mod deposit;
+mod early_exit;
+mod events;
 mod evm_delegate;
 mod internal;
 mod locking_position;
+mod merkle_claims;
 mod migrate;
+mod nft;
+mod object_voters;
+mod rounds;
+mod stale_policy;
+mod stale_sweep;
 mod timestamp_utils;
 mod types;
 mod utils;
 mod view;
+mod vote_allocations;
 mod voter;
+mod vp_curve;
 mod withdraw;
 
 #[near_bindgen]
@@ -88,6 +116,73 @@ pub struct MetaVoteContract {
     pub claimable_unlocked_mpdao: UnorderedMap<String, u128>,
     pub accumulated_unlocked_mpdao_distributed_for_claims: u128,
     pub total_unclaimed_unlocked_mpdao: u128,
+
+    // voting power checkpoints for snapshot voting (MPIPs) - added 2026-10
+    pub vp_checkpoints: UnorderedMap<String, Vector<VpCheckpoint>>,
+    pub total_vp_checkpoints: Vector<VpCheckpoint>,
+
+    // merkle-root based distributions for claims - added 2026-10
+    pub merkle_distributions: Vector<MerkleDistribution>,
+    pub merkle_claimed_bitmap: LookupMap<(u32, u64), u128>,
+
+    // stable locking position ids & NEP-171 locking position NFTs - added 2026-10
+    pub next_position_id: PositionId,
+    pub position_nfts: UnorderedMap<TokenId, NftPosition>,
+    pub position_nft_token_ids: LookupMap<PositionId, TokenId>,
+
+    // configurable voting power curve, settable by an executed MPIP - added 2026-10
+    pub vp_curve: Vec<VpCurvePoint>,
+    pub mpip_contract_id: Option<AccountId>,
+
+    // early exit of locking positions with a penalty - added 2026-10
+    pub early_exit_config: Option<EarlyExitConfig>,
+    pub early_exit_penalty_pool: u128, // mpDAO added to the next distribute_for_claims
+
+    // opt-in percentage vote allocations - added 2026-10
+    pub vote_allocations_bp: LookupMap<VoterId, Vec<VoteAllocationBp>>,
+
+    // votable app registry - added 2026-10
+    pub apps: UnorderedMap<ContractAddress, VotableApp>,
+    pub app_objects: UnorderedMap<ContractAddress, UnorderedSet<VotableObjId>>,
+    pub app_registry_enforced: bool,
+
+    // voting rounds with vote snapshots - added 2026-10
+    pub voting_rounds: Vector<VotingRound>,
+    pub active_voting_rounds: LookupMap<ContractAddress, RoundId>,
+    pub round_voter_votes: LookupMap<(RoundId, VoterId), Vec<(VotableObjId, u128)>>,
+
+    // stale-vote policy per votable app - added 2026-10
+    pub stale_policies: UnorderedMap<ContractAddress, StalePolicy>,
+
+    // permissionless stale vote sweeping - added 2026-10
+    pub stale_sweep_cursor: u64, // index in voters of the next voter to check
+    pub stale_sweep_bounty: u128, // yoctoNEAR per removed stale vote
+    pub stale_sweep_pool: u128,  // refresh fees & funds for the bounties
+
+    // vote change notifications to votable apps - added 2026-10
+    pub app_vote_listeners: LookupMap<ContractAddress, AccountId>,
+
+    // voters per votable object index - added 2026-10
+    pub object_voters: LookupMap<CryptoHash, UnorderedMap<VoterId, u128>>,
+    pub object_voters_backfill_index: u64, // voters not indexed yet, the index is complete at 0
+
+    // percentage split delegations - added 2026-10
+    pub delegations_bp: LookupMap<VoterId, Vec<DelegationBp>>,
+
+    // delegation expiry - added 2026-10
+    pub delegation_expiries: LookupMap<(VoterId, VoterId), EpochMillis>, // (delegator, delegate) => expires_at
+    pub delegation_expiry_queue: TreeMap<(VoterId, EpochMillis, VoterId), ()>, // (delegate, expires_at, delegator)
+
+    // delegate registry - added 2026-10
+    pub delegate_profiles: UnorderedMap<VoterId, DelegateProfile>,
+    pub delegate_registry_enforced: bool,
+    pub delegates_by_vp: TreeMap<(u128, VoterId), ()>, // open & paused delegates (delegated vp, delegate)
+
+    // scoped delegations - added 2026-10
+    pub delegation_scopes: LookupMap<(VoterId, VoterId), DelegationScope>, // (delegator, delegate) => scope, All if not set
+
+    // evm delegation signatures replay protection - added 2026-10
+    pub evm_delegation_nonces: LookupMap<EvmAddress, u64>,
 }
 
 #[near_bindgen]
@@ -111,6 +206,10 @@ impl MetaVoteContract {
             min_unbond_period < max_unbond_period,
             "Review the min and max locking period"
         );
+        let mut stale_policies = UnorderedMap::new(StorageKey::StalePolicies);
+        for (contract_address, policy) in default_stale_policies() {
+            stale_policies.insert(&contract_address, &policy);
+        }
         Self {
             owner_id,
             operator_id,
@@ -150,6 +249,39 @@ impl MetaVoteContract {
             claimable_unlocked_mpdao: UnorderedMap::new(StorageKey::ClaimableUnlockedMpdao),
             accumulated_unlocked_mpdao_distributed_for_claims: 0,
             total_unclaimed_unlocked_mpdao: 0,
+            vp_checkpoints: UnorderedMap::new(StorageKey::VpCheckpoints),
+            total_vp_checkpoints: Vector::new(StorageKey::TotalVpCheckpoints),
+            merkle_distributions: Vector::new(StorageKey::MerkleDistributions),
+            merkle_claimed_bitmap: LookupMap::new(StorageKey::MerkleClaimedBitmap),
+            next_position_id: 1,
+            position_nfts: UnorderedMap::new(StorageKey::PositionNfts),
+            position_nft_token_ids: LookupMap::new(StorageKey::PositionNftTokenIds),
+            vp_curve: Vec::new(),
+            mpip_contract_id: None,
+            early_exit_config: None,
+            early_exit_penalty_pool: 0,
+            vote_allocations_bp: LookupMap::new(StorageKey::VoteAllocationsBp),
+            apps: UnorderedMap::new(StorageKey::Apps),
+            app_objects: UnorderedMap::new(StorageKey::AppObjectLists),
+            app_registry_enforced: false,
+            voting_rounds: Vector::new(StorageKey::VotingRounds),
+            active_voting_rounds: LookupMap::new(StorageKey::ActiveVotingRounds),
+            round_voter_votes: LookupMap::new(StorageKey::RoundVoterVotes),
+            stale_policies,
+            stale_sweep_cursor: 0,
+            stale_sweep_bounty: 0,
+            stale_sweep_pool: 0,
+            app_vote_listeners: LookupMap::new(StorageKey::AppVoteListeners),
+            object_voters: LookupMap::new(StorageKey::ObjectVoterLists),
+            object_voters_backfill_index: 0,
+            delegations_bp: LookupMap::new(StorageKey::DelegationsBp),
+            delegation_expiries: LookupMap::new(StorageKey::DelegationExpiries),
+            delegation_expiry_queue: TreeMap::new(StorageKey::DelegationExpiryQueue),
+            delegate_profiles: UnorderedMap::new(StorageKey::DelegateProfiles),
+            delegate_registry_enforced: false,
+            delegates_by_vp: TreeMap::new(StorageKey::DelegatesByVp),
+            delegation_scopes: LookupMap::new(StorageKey::DelegationScopes),
+            evm_delegation_nonces: LookupMap::new(StorageKey::EvmDelegationNonces),
         }
     }
 
@@ -160,20 +292,37 @@ impl MetaVoteContract {
     pub fn set_stnear_contract(&mut self, stnear_contract: AccountId) {
         assert_one_yocto();
         self.assert_only_owner();
+        emit_config_change("stnear_token_contract_address", &stnear_contract);
         self.stnear_token_contract_address = stnear_contract;
     }
     #[payable]
     pub fn set_operator_id(&mut self, operator_id: AccountId) {
         assert_one_yocto();
         self.assert_only_owner();
+        emit_config_change("operator_id", &operator_id);
         self.operator_id = operator_id;
     }
     #[payable]
     pub fn set_owner_id(&mut self, owner_id: AccountId) {
         assert_one_yocto();
         self.assert_only_owner();
+        emit_config_change("owner_id", &owner_id);
         self.owner_id = owner_id;
     }
+    /// the MPIP (governance) contract can execute owner config actions, e.g. set_vp_curve
+    #[payable]
+    pub fn set_mpip_contract_id(&mut self, mpip_contract_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_only_owner();
+        emit_config_change(
+            "mpip_contract_id",
+            mpip_contract_id
+                .as_ref()
+                .map(|account_id| account_id.to_string())
+                .unwrap_or_default(),
+        );
+        self.mpip_contract_id = mpip_contract_id;
+    }
 
     // *******************************
     // * Register for Airdrops/Gifts *
@@ -183,6 +332,7 @@ impl MetaVoteContract {
     pub fn update_registration_cost(&mut self, new_cost: U128String) {
         self.assert_only_owner();
         self.registration_cost = new_cost.0;
+        emit_config_change("registration_cost", self.registration_cost);
     }
 
     // for airdrops/rewards
@@ -277,10 +427,14 @@ impl MetaVoteContract {
     // * Unlocking *
     // *************
 
-    pub fn unlock_position(&mut self, index: PositionIndex) {
+    pub fn unlock_position(&mut self, position_id: PositionId) {
         let voter_id: String = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
         let mut locking_position = voter.get_position(index);
+        self.assert_not_nft_position(&locking_position);
 
         let voting_power_to_remove = locking_position.voting_power;
         locking_position.unlocking_started_at = Some(get_current_epoch_millis());
@@ -289,30 +443,37 @@ impl MetaVoteContract {
         // make sure there was enough available voting power
         self.require_vp_available(&voter_id, &mut voter);
 
-        log!(
-            "UNLOCK: {} unlocked position {}.",
-            &voter_id.to_string(),
-            index
-        );
+        MetaVoteEvent::Unlock(UnlockData {
+            account_id: &voter_id,
+            position_id,
+            amount: locking_position.amount.into(),
+            voting_power: voting_power_to_remove.into(),
+        })
+        .emit();
 
         self.total_voting_power = self
             .total_voting_power
             .saturating_sub(voting_power_to_remove);
 
+        self.internal_after_vp_change(&voter_id, &mut voter);
         self.voters.insert(&voter_id, &voter);
     }
 
-    pub fn unlock_partial_position(&mut self, index: PositionIndex, amount: U128String) {
+    pub fn unlock_partial_position(&mut self, position_id: PositionId, amount: U128String) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
         let mut locking_position = voter.get_position(index);
+        self.assert_not_nft_position(&locking_position);
 
         let locking_period = locking_position.locking_period;
         let amount = MpDAOAmount::from(amount);
 
         // If the amount equals the total, then the unlock is not partial.
         if amount == locking_position.amount {
-            return self.unlock_position(index);
+            return self.unlock_position(position_id);
         }
         require!(locking_position.amount > amount, "Amount too large!");
         assert!(
@@ -321,7 +482,12 @@ impl MetaVoteContract {
             self.min_deposit_amount
         );
         assert_at_least_1_mpdao(amount);
-        let remove_voting_power = utils::calculate_voting_power(amount, locking_period);
+        // proportional to the position vp, the position may predate the current curve
+        let remove_voting_power = proportional(
+            locking_position.voting_power,
+            amount,
+            locking_position.amount,
+        );
 
         // Create a NEW unlocking position
         self.create_unlocking_position(&mut voter, amount, locking_period, remove_voting_power);
@@ -335,13 +501,16 @@ impl MetaVoteContract {
         // make sure there was enough available voting power
         self.require_vp_available(&voter_id, &mut voter);
 
-        log!(
-            "UNLOCK: {} partially unlocked position {}.",
-            &voter_id.to_string(),
-            index
-        );
+        MetaVoteEvent::Unlock(UnlockData {
+            account_id: &voter_id,
+            position_id,
+            amount: amount.into(),
+            voting_power: remove_voting_power.into(),
+        })
+        .emit();
 
         self.total_voting_power = self.total_voting_power.saturating_sub(remove_voting_power);
+        self.internal_after_vp_change(&voter_id, &mut voter);
         self.voters.insert(&voter_id, &voter);
     }
 
@@ -349,9 +518,16 @@ impl MetaVoteContract {
     // * extend locking position days *
     // ********************************
 
-    pub fn locking_position_extend_days(&mut self, index: PositionIndex, new_locking_period: Days) {
+    pub fn locking_position_extend_days(
+        &mut self,
+        position_id: PositionId,
+        new_locking_period: Days,
+    ) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
         let mut locking_position = voter.get_position(index);
 
         // position should be locked
@@ -364,19 +540,27 @@ impl MetaVoteContract {
             "new auto-lock period should be greater than previous one"
         );
 
-        log!(
-            "EXTEND-TIME: {} position #{} {} days",
-            &voter_id.to_string(),
-            index,
-            new_locking_period
-        );
-
         let old_voting_power = locking_position.voting_power;
         let new_voting_power =
-            utils::calculate_voting_power(locking_position.amount, new_locking_period);
-
-        // update to new total-voting-power (add delta)
-        self.total_voting_power += new_voting_power - old_voting_power;
+            self.internal_calculate_voting_power(locking_position.amount, new_locking_period);
+
+        MetaVoteEvent::Extend(ExtendData {
+            account_id: &voter_id,
+            position_id,
+            locking_period: new_locking_period,
+            voting_power: new_voting_power.into(),
+        })
+        .emit();
+
+        // update to new total-voting-power (add delta), the vp can decrease
+        // if the vp curve changed since the position was locked
+        if new_voting_power >= old_voting_power {
+            self.total_voting_power += new_voting_power - old_voting_power;
+        } else {
+            self.total_voting_power = self
+                .total_voting_power
+                .saturating_sub(old_voting_power - new_voting_power);
+        }
 
         // update position
         locking_position.locking_period = new_locking_period;
@@ -385,24 +569,166 @@ impl MetaVoteContract {
         // save
         voter.locking_positions.replace(index, &locking_position);
         // after saving position, update available voting power
-        self.update_vp_available(&voter_id, &mut voter);
+        if new_voting_power >= old_voting_power {
+            self.update_vp_available(&voter_id, &mut voter);
+        } else {
+            // make sure there was enough available voting power
+            self.require_vp_available(&voter_id, &mut voter);
+        }
+        self.internal_after_vp_change(&voter_id, &mut voter);
 
         // save voter
         self.voters.insert(&voter_id, &voter);
     }
 
+    // ***************************
+    // * Merge & split positions *
+    // ***************************
+
+    /// Merges locked positions into the locked position for target_days,
+    /// target_days must be >= the days of every merged position.
+    /// Returns the resulting position id
+    pub fn merge_locking_positions(
+        &mut self,
+        position_ids: Vec<PositionId>,
+        target_days: Days,
+    ) -> PositionId {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        require!(
+            position_ids.len() >= 2,
+            "At least two positions are required to merge."
+        );
+        self.assert_unbond_period(target_days);
+        self.internal_assign_position_ids(&mut voter);
+        let mut indexes = voter.get_position_indexes(&position_ids);
+        indexes.sort_unstable();
+        indexes.dedup();
+        require!(
+            indexes.len() == position_ids.len(),
+            "Duplicated position id."
+        );
+
+        let mut merged_amount = 0;
+        let mut merged_voting_power = 0;
+        // from the last index, swap_remove does not move the lower indexes
+        for index in indexes.into_iter().rev() {
+            let locking_position = voter.get_position(index);
+            require!(
+                locking_position.is_locked(),
+                "Only locked positions can be merged."
+            );
+            self.assert_not_nft_position(&locking_position);
+            require!(
+                target_days >= locking_position.locking_period,
+                "target_days cannot be less than the days of a merged position."
+            );
+            // a position with target_days is merged in place
+            if locking_position.locking_period == target_days {
+                continue;
+            }
+            merged_amount += locking_position.amount;
+            merged_voting_power += locking_position.voting_power;
+            voter.remove_position(index);
+        }
+        self.total_voting_power -= merged_voting_power;
+        let position_id = self.internal_lock_in_position(&mut voter, merged_amount, target_days);
+        // the positions vp could predate the current curve
+        self.require_vp_available(&voter_id, &mut voter);
+
+        MetaVoteEvent::Merge(MergeData {
+            account_id: &voter_id,
+            position_ids: &position_ids,
+            position_id,
+            locking_period: target_days,
+            voting_power: voter
+                .get_position(voter.get_position_index(position_id))
+                .voting_power
+                .into(),
+        })
+        .emit();
+        self.internal_after_vp_change(&voter_id, &mut voter);
+        self.voters.insert(&voter_id, &voter);
+        position_id
+    }
+
+    /// Moves amount from a locked position to the locked position for new_days,
+    /// new_days must be greater than the position days.
+    /// Returns the position id that received the amount
+    pub fn split_locking_position(
+        &mut self,
+        position_id: PositionId,
+        amount: U128String,
+        new_days: Days,
+    ) -> PositionId {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
+        let mut locking_position = voter.get_position(index);
+        let amount = MpDAOAmount::from(amount);
+        require!(
+            locking_position.is_locked(),
+            "Only locked positions can be split."
+        );
+        self.assert_not_nft_position(&locking_position);
+        // locked positions are unique by days, same days would be the same position
+        require!(
+            new_days > locking_position.locking_period,
+            "new_days should be greater than the position days."
+        );
+        self.assert_unbond_period(new_days);
+        require!(locking_position.amount > amount, "Amount too large!");
+        assert!(
+            amount >= self.min_deposit_amount
+                && (locking_position.amount - amount) >= self.min_deposit_amount,
+            "A locking position cannot have less than {} mpDAO",
+            self.min_deposit_amount
+        );
+
+        // proportional to the position vp, the position may predate the current curve
+        let remove_voting_power = proportional(
+            locking_position.voting_power,
+            amount,
+            locking_position.amount,
+        );
+        locking_position.voting_power -= remove_voting_power;
+        locking_position.amount -= amount;
+        voter.locking_positions.replace(index, &locking_position);
+        self.total_voting_power -= remove_voting_power;
+        let new_position_id = self.internal_lock_in_position(&mut voter, amount, new_days);
+        self.require_vp_available(&voter_id, &mut voter);
+
+        MetaVoteEvent::Split(SplitData {
+            account_id: &voter_id,
+            position_id,
+            new_position_id,
+            amount: amount.into(),
+            locking_period: new_days,
+        })
+        .emit();
+        self.internal_after_vp_change(&voter_id, &mut voter);
+        self.voters.insert(&voter_id, &voter);
+        new_position_id
+    }
+
     // ***********
     // * Re-Lock *
     // ***********
 
     pub fn relock_position(
         &mut self,
-        index: PositionIndex,
+        position_id: PositionId,
         locking_period: Days,
         amount_from_balance: U128String,
     ) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
         let locking_position = voter.get_position(index);
 
         // Check voter balance and unlocking position amount.
@@ -433,11 +759,14 @@ impl MetaVoteContract {
             );
         }
 
-        log!(
-            "RELOCK: {} relocked position {}.",
-            &voter_id.to_string(),
-            index
-        );
+        MetaVoteEvent::Relock(RelockData {
+            account_id: &voter_id,
+            position_id: Some(position_id),
+            amount_from_position: locking_position.amount.into(),
+            amount_from_balance: amount_from_balance.into(),
+            locking_period,
+        })
+        .emit();
         let amount = locking_position.amount + amount_from_balance;
         voter.remove_position(index);
         voter.balance -= amount_from_balance;
@@ -446,13 +775,16 @@ impl MetaVoteContract {
 
     pub fn relock_partial_position(
         &mut self,
-        index: PositionIndex,
+        position_id: PositionId,
         amount_from_position: U128String,
         locking_period: Days,
         amount_from_balance: U128String,
     ) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let index = voter.get_position_index(position_id);
         let mut locking_position = voter.get_position(index);
 
         // Check voter balance and unlocking position amount.
@@ -505,24 +837,28 @@ impl MetaVoteContract {
 
             locking_position.amount = new_amount;
             locking_position.voting_power =
-                utils::calculate_voting_power(new_amount, locking_position.locking_period);
+                self.internal_calculate_voting_power(new_amount, locking_position.locking_period);
             voter.locking_positions.replace(index, &locking_position);
         } else {
             voter.balance += locking_position.amount - amount_from_position;
             voter.remove_position(index);
         }
 
-        log!(
-            "RELOCK: {} partially relocked position {}.",
-            &voter_id.to_string(),
-            index
-        );
+        MetaVoteEvent::Relock(RelockData {
+            account_id: &voter_id,
+            position_id: Some(position_id),
+            amount_from_position: amount_from_position.into(),
+            amount_from_balance: amount_from_balance.into(),
+            locking_period,
+        })
+        .emit();
         voter.balance -= amount_from_balance;
         self.deposit_locking_position(amount, locking_period, &voter_id, &mut voter);
     }
 
     pub fn relock_from_balance(&mut self, locking_period: Days, amount_from_balance: U128String) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
 
         let amount = amount_from_balance.0;
@@ -538,7 +874,14 @@ impl MetaVoteContract {
             self.min_deposit_amount
         );
 
-        log!("RELOCK: {} relocked position.", &voter_id.to_string());
+        MetaVoteEvent::Relock(RelockData {
+            account_id: &voter_id,
+            position_id: None,
+            amount_from_position: 0.into(),
+            amount_from_balance: amount.into(),
+            locking_period,
+        })
+        .emit();
         voter.balance -= amount;
         self.deposit_locking_position(amount, locking_period, &voter_id, &mut voter);
     }
@@ -549,10 +892,12 @@ impl MetaVoteContract {
 
     // clear SEVERAL fully unlocked positions for env::predecessor_account_id()
     // and increases their balance
-    pub fn clear_locking_position(&mut self, position_index_list: Vec<PositionIndex>) {
-        require!(position_index_list.len() > 0, "Index list is empty.");
+    pub fn clear_locking_position(&mut self, position_id_list: Vec<PositionId>) {
+        require!(position_id_list.len() > 0, "Position id list is empty.");
         let voter_id = env::predecessor_account_id().as_str().to_string();
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_assign_position_ids(&mut voter);
+        let position_index_list = voter.get_position_indexes(&position_id_list);
         voter.clear_fully_unlocked_positions(position_index_list);
         self.voters.insert(&voter_id, &voter);
     }
@@ -631,6 +976,12 @@ impl MetaVoteContract {
         {
             panic!("Cannot delegate votes to someone who is also a delegator.");
         }
+
+        // Check 4: when the delegate registry is enforced, the delegate accepts delegations
+        self.assert_delegate_accepts(
+            votable_object_id,
+            &self.internal_get_delegation_scope(voter_id, votable_object_id),
+        );
     }
 
     fn internal_vote(
@@ -640,6 +991,8 @@ impl MetaVoteContract {
         contract_address: ContractAddress,
         votable_object_id: VotableObjId,
     ) {
+        // return expired delegations first
+        self.internal_touch_delegations(voter_id);
         // Validate delegation rules if this is a delegation operation
         self.validate_delegation_rules(voter_id, &contract_address, &votable_object_id);
 
@@ -657,13 +1010,13 @@ impl MetaVoteContract {
         // save voter info
         self.voters.insert(&voter_id, &voter);
 
-        log!(
-            "VOTE: {} gave {} votes for object {} at address {}.",
-            &voter_id,
-            voting_power.to_string(),
-            &votable_object_id,
-            contract_address.as_str()
-        );
+        MetaVoteEvent::Vote(VoteData {
+            account_id: voter_id,
+            contract_address: &contract_address,
+            votable_object_id: &votable_object_id,
+            voting_power: voting_power.into(),
+        })
+        .emit();
     }
 
     fn internal_create_voting_position(
@@ -674,12 +1027,17 @@ impl MetaVoteContract {
         contract_address: &ContractAddress,
         votable_object_id: &VotableObjId,
     ) {
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_seed_vp_checkpoints(votable_object_id);
+        }
         assert!(
             voter.available_voting_power >= voting_power,
             "Not enough free voting power. You have {}, requested {}.",
             voter.available_voting_power,
             voting_power
         );
+        self.assert_vote_pool_room(voter_id, voter, contract_address, voting_power);
+        self.assert_can_receive_votes(contract_address, votable_object_id, voting_power);
         assert!(
             voter.vote_positions.len() <= self.max_voting_positions as u64,
             "Cannot exceed {} voting positions.",
@@ -699,13 +1057,28 @@ impl MetaVoteContract {
 
         if contract_address == DELEGATED_CONTRACT_CODE {
             // delegate votes
-            self.internal_add_delegated_voting_power(votable_object_id, voting_power);
+            let scope = self.internal_get_delegation_scope(voter_id, votable_object_id);
+            self.internal_add_delegated_voting_power(votable_object_id, voting_power, &scope);
         }
 
         // Store timestamp for this vote
         self.store_vote_timestamp(voter_id, contract_address, votable_object_id);
         // Update Meta Vote state.
         self.internal_increase_total_votes(voting_power, &contract_address, &votable_object_id);
+        self.internal_index_object_voter(voter_id, contract_address, votable_object_id, votes);
+        self.notify_votes_changed(
+            voter_id,
+            contract_address,
+            votable_object_id,
+            votes - voting_power,
+            votes,
+        );
+
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            // mpip voting power changed for both delegator and delegate
+            self.write_vp_checkpoints(voter_id, voter);
+            self.internal_after_delegate_vp_change(votable_object_id);
+        }
     }
 
     /// Adjusts voting power for an existing vote position.
@@ -718,12 +1091,12 @@ impl MetaVoteContract {
         votable_object_id: VotableObjId,
     ) {
         let voter_id = env::predecessor_account_id().to_string();
+        self.internal_touch_delegations(&voter_id);
         let mut voter = self.internal_get_voter_or_panic(&voter_id);
         let voting_power = u128::from(voting_power);
 
-        let mut votes_for_address =
-            voter.get_vote_position_for_address(&voter_id, &contract_address);
-        let mut votes = votes_for_address
+        let votes = voter
+            .get_vote_position_for_address(&voter_id, &contract_address)
             .get(&votable_object_id)
             .expect("Rebalance not allowed for nonexisting Votable Object.");
 
@@ -734,13 +1107,46 @@ impl MetaVoteContract {
         if voting_power == 0 {
             return self.unvote(contract_address, votable_object_id);
         }
+        if voting_power < votes {
+            self.assert_votes_not_locked(&contract_address, &votable_object_id);
+        }
+
+        self.internal_rebalance_voting_position(
+            &voter_id,
+            &mut voter,
+            voting_power,
+            &contract_address,
+            &votable_object_id,
+        );
+        self.voters.insert(&voter_id, &voter);
+    }
+
+    /// changes an existing vote to voting_power (not 0), emits the rebalance event
+    fn internal_rebalance_voting_position(
+        &mut self,
+        voter_id: &String,
+        voter: &mut Voter,
+        voting_power: u128,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) {
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_seed_vp_checkpoints(votable_object_id);
+        }
+        let mut votes_for_address = voter.get_vote_position_for_address(voter_id, contract_address);
+        let mut votes = votes_for_address
+            .get(votable_object_id)
+            .expect("Rebalance not allowed for nonexisting Votable Object.");
+        let previous_voting_power = votes;
 
         if votes < voting_power {
             // Increase votes.
             let additional_votes = voting_power - votes;
 
             // Validate delegation rules when increasing delegation votes
-            self.validate_delegation_rules(&voter_id, &contract_address, &votable_object_id);
+            self.validate_delegation_rules(voter_id, contract_address, votable_object_id);
+            self.assert_can_receive_votes(contract_address, votable_object_id, additional_votes);
+            self.assert_vote_pool_room(voter_id, voter, contract_address, additional_votes);
 
             assert!(
                 voter.available_voting_power >= additional_votes,
@@ -752,25 +1158,22 @@ impl MetaVoteContract {
             votes += additional_votes;
 
             // Update timestamp for modified vote
-            self.store_vote_timestamp(&voter_id, &contract_address, &votable_object_id);
-
-            log!(
-                "VOTE: {} increased to {} votes for object {} at address {}.",
-                &voter_id,
-                voting_power.to_string(),
-                &votable_object_id,
-                contract_address.as_str()
-            );
+            self.store_vote_timestamp(voter_id, contract_address, votable_object_id);
 
             self.internal_increase_total_votes(
                 additional_votes,
-                &contract_address,
-                &votable_object_id,
+                contract_address,
+                votable_object_id,
             );
 
             if contract_address == DELEGATED_CONTRACT_CODE {
                 // When increasing delegation, add only the additional votes to the delegate
-                self.internal_add_delegated_voting_power(&votable_object_id, additional_votes);
+                let scope = self.internal_get_delegation_scope(voter_id, votable_object_id);
+                self.internal_add_delegated_voting_power(
+                    votable_object_id,
+                    additional_votes,
+                    &scope,
+                );
             }
         } else {
             // Decrease votes.
@@ -779,32 +1182,49 @@ impl MetaVoteContract {
             votes -= remove_votes;
 
             // Update timestamp for modified vote
-            self.store_vote_timestamp(&voter_id, &contract_address, &votable_object_id);
-
-            log!(
-                "VOTE: {} decreased to {} votes for object {} at address {}.",
-                &voter_id,
-                voting_power.to_string(),
-                &votable_object_id,
-                contract_address.as_str()
-            );
+            self.store_vote_timestamp(voter_id, contract_address, votable_object_id);
 
             self.state_internal_decrease_total_votes_for_address(
                 remove_votes,
-                &contract_address,
-                &votable_object_id,
+                contract_address,
+                votable_object_id,
             );
 
             if contract_address == DELEGATED_CONTRACT_CODE {
                 // remove delegated vp
-                self.internal_remove_delegated_voting_power(&votable_object_id, remove_votes);
+                let scope = self.internal_get_delegation_scope(voter_id, votable_object_id);
+                self.internal_remove_delegated_voting_power(
+                    votable_object_id,
+                    remove_votes,
+                    &scope,
+                );
             }
         }
-        votes_for_address.insert(&votable_object_id, &votes);
+        votes_for_address.insert(votable_object_id, &votes);
         voter
             .vote_positions
-            .insert(&contract_address, &votes_for_address);
-        self.voters.insert(&voter_id, &voter);
+            .insert(contract_address, &votes_for_address);
+        self.internal_index_object_voter(voter_id, contract_address, votable_object_id, votes);
+        self.notify_votes_changed(
+            voter_id,
+            contract_address,
+            votable_object_id,
+            previous_voting_power,
+            votes,
+        );
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.write_vp_checkpoints(voter_id, voter);
+            self.internal_after_delegate_vp_change(votable_object_id);
+        }
+
+        MetaVoteEvent::Rebalance(RebalanceData {
+            account_id: voter_id,
+            contract_address,
+            votable_object_id,
+            previous_voting_power: previous_voting_power.into(),
+            voting_power: voting_power.into(),
+        })
+        .emit();
     }
 
     fn internal_remove_voting_position(
@@ -814,6 +1234,9 @@ impl MetaVoteContract {
         contract_address: &ContractAddress,
         votable_object_id: &VotableObjId,
     ) {
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_seed_vp_checkpoints(votable_object_id);
+        }
         // update this voter struct
         let mut user_votes_for_app =
             voter.get_vote_position_for_address(&voter_id, &contract_address);
@@ -835,9 +1258,18 @@ impl MetaVoteContract {
         // Remove timestamp for this vote
         self.remove_vote_timestamp(voter_id, contract_address, votable_object_id);
 
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.internal_remove_delegation_expiry(voter_id, votable_object_id);
+        }
+
         if contract_address == DELEGATED_CONTRACT_CODE {
             // remove delegated votes
-            self.internal_remove_delegated_voting_power(votable_object_id, user_vote_for_object);
+            let scope = self.internal_get_delegation_scope(voter_id, votable_object_id);
+            self.internal_remove_delegated_voting_power(
+                votable_object_id,
+                user_vote_for_object,
+                &scope,
+            );
         }
 
         // Update Meta Vote global state unordered maps
@@ -846,65 +1278,239 @@ impl MetaVoteContract {
             &contract_address,
             &votable_object_id,
         );
+        self.internal_index_object_voter(voter_id, contract_address, votable_object_id, 0);
+        self.notify_votes_changed(
+            voter_id,
+            contract_address,
+            votable_object_id,
+            user_vote_for_object,
+            0,
+        );
+
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            self.write_vp_checkpoints(voter_id, voter);
+            self.internal_after_delegate_vp_change(votable_object_id);
+        }
+
+        MetaVoteEvent::Unvote(VoteData {
+            account_id: voter_id,
+            contract_address,
+            votable_object_id,
+            voting_power: user_vote_for_object.into(),
+        })
+        .emit();
     }
 
     pub fn unvote(&mut self, contract_address: ContractAddress, votable_object_id: VotableObjId) {
         let voter_id = env::predecessor_account_id().as_str().to_string();
-        log!(
-            "UNVOTE: {} unvoted object {} at address {}.",
-            &env::predecessor_account_id(),
-            &votable_object_id,
-            contract_address.as_str()
-        );
         self.internal_unvote(&voter_id, &contract_address, &votable_object_id, false)
     }
 
+    /// Sets all the caller votes for contract_address in one call, atomically.
+    /// Objects not in `allocations` (or with voting_power 0) are unvoted.
+    /// Decreases and removals are applied first, to free voting power for increases and new votes.
+    pub fn set_vote_allocation(
+        &mut self,
+        contract_address: ContractAddress,
+        allocations: Vec<(VotableObjId, U128String)>,
+    ) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        let mut new_votes = std::collections::BTreeMap::new();
+        for (votable_object_id, voting_power) in allocations {
+            require!(
+                new_votes
+                    .insert(votable_object_id, voting_power.0)
+                    .is_none(),
+                "Duplicated votable object id."
+            );
+        }
+        let current_votes: std::collections::BTreeMap<VotableObjId, u128> = voter
+            .vote_positions
+            .get(&contract_address)
+            .map(|votes_for_address| votes_for_address.iter().collect())
+            .unwrap_or_default();
+
+        // remove & decrease
+        for (votable_object_id, votes) in current_votes.iter() {
+            let voting_power = new_votes.get(votable_object_id).copied().unwrap_or(0);
+            if voting_power == 0 {
+                self.assert_vote_can_be_removed(
+                    &voter_id,
+                    &contract_address,
+                    votable_object_id,
+                    false,
+                );
+                self.internal_remove_voting_position(
+                    &voter_id,
+                    &mut voter,
+                    &contract_address,
+                    votable_object_id,
+                );
+            } else if voting_power < *votes {
+                self.assert_votes_not_locked(&contract_address, votable_object_id);
+                self.internal_rebalance_voting_position(
+                    &voter_id,
+                    &mut voter,
+                    voting_power,
+                    &contract_address,
+                    votable_object_id,
+                );
+            }
+        }
+        // increase & vote
+        for (votable_object_id, voting_power) in new_votes.iter() {
+            match current_votes.get(votable_object_id) {
+                Some(votes) if voting_power > votes => {
+                    self.internal_rebalance_voting_position(
+                        &voter_id,
+                        &mut voter,
+                        *voting_power,
+                        &contract_address,
+                        votable_object_id,
+                    );
+                }
+                None if *voting_power > 0 => {
+                    self.validate_delegation_rules(&voter_id, &contract_address, votable_object_id);
+                    self.internal_create_voting_position(
+                        &voter_id,
+                        &mut voter,
+                        *voting_power,
+                        &contract_address,
+                        votable_object_id,
+                    );
+                    MetaVoteEvent::Vote(VoteData {
+                        account_id: &voter_id,
+                        contract_address: &contract_address,
+                        votable_object_id,
+                        voting_power: (*voting_power).into(),
+                    })
+                    .emit();
+                }
+                _ => {}
+            }
+        }
+        self.voters.insert(&voter_id, &voter);
+    }
+
     fn internal_unvote(
         &mut self,
         voter_id: &String,
         contract_address: &ContractAddress,
         votable_object_id: &VotableObjId,
-        bypass_delegation_restriction: bool,
+        bypass_min_hold: bool,
+    ) {
+        self.internal_touch_delegations(voter_id);
+        self.assert_vote_can_be_removed(
+            voter_id,
+            contract_address,
+            votable_object_id,
+            bypass_min_hold,
+        );
+
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        self.internal_remove_voting_position(
+            &voter_id,
+            &mut voter,
+            &contract_address,
+            &votable_object_id,
+        );
+        // save voter
+        self.voters.insert(&voter_id, &voter);
+    }
+
+    /// the app policy can require a min time since the vote or last refresh, delegations
+    /// always wait at least 7 days, whatever the policy
+    fn internal_min_hold_ms(&self, contract_address: &ContractAddress) -> u64 {
+        let policy_min_hold_ms = self
+            .stale_policies
+            .get(contract_address)
+            .map(|policy| policy.min_hold_ms)
+            .unwrap_or(0);
+        if contract_address == DELEGATED_CONTRACT_CODE {
+            std::cmp::max(policy_min_hold_ms, crate::timestamp_utils::SEVEN_DAYS_MS)
+        } else {
+            policy_min_hold_ms
+        }
+    }
+
+    fn assert_vote_can_be_removed(
+        &self,
+        voter_id: &String,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+        bypass_min_hold: bool,
     ) {
-        // verify if unvoting delegated vote: must wait at least 7 days since last refresh
         // (unless bypassing the restriction, e.g., for operator cleanup of stale votes)
-        if contract_address == DELEGATED_CONTRACT_CODE && !bypass_delegation_restriction {
+        let min_hold_ms = self.internal_min_hold_ms(contract_address);
+        if min_hold_ms > 0 && !bypass_min_hold {
             let vote_timestamp =
                 self.get_vote_timestamp(voter_id, contract_address, votable_object_id);
             let time_since_last_refresh = env::block_timestamp_ms().saturating_sub(vote_timestamp);
             require!(
-                time_since_last_refresh >= crate::timestamp_utils::SEVEN_DAYS_MS,
+                time_since_last_refresh >= min_hold_ms,
                 format!(
-                    "Cannot unvote delegated votes until 7 days have passed since last refresh. Time remaining: {} ms",
-                    crate::timestamp_utils::SEVEN_DAYS_MS.saturating_sub(time_since_last_refresh)
+                    "Cannot unvote until {} ms have passed since last refresh. Time remaining: {} ms",
+                    min_hold_ms,
+                    min_hold_ms.saturating_sub(time_since_last_refresh)
                 )
             );
         }
 
-        // verify if the votes are locked (for example last 48hs of grants voting up to 20 days after)
-        if let Some(lock_votes_in_address) = &self.lock_votes_in_address {
-            if self.lock_votes_in_end_timestamp_ms > env::block_timestamp_ms()
-                && lock_votes_in_address == contract_address
-            {
-                let votable_object_id_filter = format!(" #{} ", self.lock_votes_in_numeric_id);
-                if votable_object_id.contains(&votable_object_id_filter) {
-                    panic!(
-                        "you can not remove votes here until timestamp_ms {}",
-                        self.lock_votes_in_end_timestamp_ms
-                    )
-                }
+        self.assert_votes_not_locked(contract_address, votable_object_id);
+    }
+
+    fn assert_votes_not_locked(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) {
+        if self.is_round_frozen(contract_address) {
+            panic!("you can not remove votes here until the voting round is finalized")
+        }
+        if self.are_votes_locked(contract_address, votable_object_id) {
+            panic!(
+                "you can not remove votes here until timestamp_ms {}",
+                self.lock_votes_in_end_timestamp_ms
+            )
+        }
+    }
+
+    /// votes are locked, for example last 48hs of grants voting up to 20 days after.
+    /// Voting rounds freeze all the votes of their app, the lock_votes_in_* filter is legacy.
+    fn are_votes_locked(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) -> bool {
+        if self.is_round_frozen(contract_address) {
+            return true;
+        }
+        match &self.lock_votes_in_address {
+            Some(lock_votes_in_address) => {
+                self.lock_votes_in_end_timestamp_ms > env::block_timestamp_ms()
+                    && lock_votes_in_address == contract_address
+                    && votable_object_id.contains(&format!(" #{} ", self.lock_votes_in_numeric_id))
             }
+            None => false,
         }
+    }
 
-        let mut voter = self.internal_get_voter_or_panic(&voter_id);
-        self.internal_remove_voting_position(
-            &voter_id,
-            &mut voter,
-            &contract_address,
-            &votable_object_id,
-        );
-        // save voter
-        self.voters.insert(&voter_id, &voter);
+    fn internal_remove_stale_vote(
+        &mut self,
+        voter_id: &String,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) {
+        // the unvote event is followed by this one, to flag the unvote as stale removal
+        self.internal_unvote(voter_id, contract_address, votable_object_id, true);
+        MetaVoteEvent::StaleVoteRemoved(StaleVoteData {
+            account_id: voter_id,
+            contract_address,
+            votable_object_id,
+        })
+        .emit();
     }
 
     // remove votes that are verified as stale
@@ -913,7 +1519,11 @@ impl MetaVoteContract {
         let mut count = 0;
         for r in list_to_remove {
             if self.verify_vote_is_stale(&r.voter_id, &r.contract_address, &r.votable_object_id) {
-                self.internal_unvote(&r.voter_id, &r.contract_address, &r.votable_object_id, true);
+                self.internal_remove_stale_vote(
+                    &r.voter_id,
+                    &r.contract_address,
+                    &r.votable_object_id,
+                );
                 count += 1;
             } else {
                 log!(
@@ -927,45 +1537,56 @@ impl MetaVoteContract {
         log!("Removed {} stale votes.", count);
     }
 
-    /// Refresh validator votes for a user
+    /// Refresh the votes of a user for the apps with a stale policy.
     /// Returns how many votes were updated.
-    // refreshing costs 0.01 NEAR, call it with 0.01 NEAR attached
+    // refreshing costs the highest refresh_fee of the voter apps (see get_refresh_fee), the excess is returned
     #[payable]
     pub fn refresh_vote_timestamps(&mut self, voter_id: &AccountId) -> u16 {
-        // refreshing costs 0.01 NEAR
-        require!(
-            env::attached_deposit() == ONE_NEAR / 100,
-            "Attach exactly 0.01 NEAR to refresh votes timestamps."
-        );
         let mut refreshed: u16 = 0;
+        let mut refresh_fee: u128 = 0;
         // Check if the voter exists in the registry
         if let Some(voter) = self.voters.get(&voter_id.to_string()) {
             // Iterate through all vote positions for this voter
-            let to_update_contract_addresses = ["metastaking.app", DELEGATED_CONTRACT_CODE];
             for contract_address in voter.vote_positions.keys_as_vector().iter() {
-                // Only refresh if the vote is for validators (contract_address is 'metastaking.app')
-                // or for delegated votes
-                if to_update_contract_addresses.contains(&contract_address.as_str()) {
-                    if let Some(votes_for_address) = voter.vote_positions.get(&contract_address) {
-                        // Iterate each votable object ID
-                        for votable_object_id in votes_for_address.keys_as_vector().iter() {
-                            self.store_vote_timestamp(
-                                &voter_id.to_string(),
-                                &contract_address,
-                                &votable_object_id,
-                            );
-                            refreshed += 1;
-                        }
+                // Only refresh if staleness applies to the app
+                let policy = match self.internal_get_stale_policy(&contract_address) {
+                    Some(policy) => policy,
+                    None => continue,
+                };
+                if let Some(votes_for_address) = voter.vote_positions.get(&contract_address) {
+                    // Iterate each votable object ID
+                    for votable_object_id in votes_for_address.keys_as_vector().iter() {
+                        self.store_vote_timestamp(
+                            &voter_id.to_string(),
+                            &contract_address,
+                            &votable_object_id,
+                        );
+                        refreshed += 1;
                     }
+                    refresh_fee = std::cmp::max(refresh_fee, policy.refresh_fee.0);
                 }
             }
         }
+        let attached = env::attached_deposit();
+        require!(
+            attached >= refresh_fee,
+            format!(
+                "Attach {} yoctoNEAR to refresh votes timestamps.",
+                refresh_fee
+            )
+        );
+        // refresh fees pay the stale vote sweeping bounties
+        self.stale_sweep_pool += refresh_fee;
+        if attached > refresh_fee {
+            Promise::new(env::predecessor_account_id()).transfer(attached - refresh_fee);
+        }
         refreshed
     }
 
     // *********
     // * Admin *
     // *********
+    /// legacy single global lock filter, prefer create_voting_round
     pub fn set_lock_in_vote_filters(
         &mut self,
         end_timestamp_ms: u64,
@@ -976,6 +1597,13 @@ impl MetaVoteContract {
         self.lock_votes_in_end_timestamp_ms = end_timestamp_ms;
         self.lock_votes_in_address = votable_address;
         self.lock_votes_in_numeric_id = votable_numeric_id;
+        emit_config_change(
+            "lock_in_vote_filters",
+            format!(
+                "{} {:?} {}",
+                end_timestamp_ms, self.lock_votes_in_address, votable_numeric_id
+            ),
+        );
     }
     pub fn get_lock_in_vote_filters(self) -> (u64, Option<String>, u16) {
         (
@@ -990,17 +1618,20 @@ impl MetaVoteContract {
         assert_one_yocto();
         self.assert_only_owner();
         self.min_unbond_period = new_min_unbond_period;
+        emit_config_change("min_unbond_period", new_min_unbond_period);
     }
     #[payable]
     pub fn update_max_unbond_period(&mut self, new_max_unbond_period: Days) {
         assert_one_yocto();
         self.assert_only_owner();
         self.max_unbond_period = new_max_unbond_period;
+        emit_config_change("max_unbond_period", new_max_unbond_period);
     }
     #[payable]
     pub fn set_prev_gov_contract(&mut self, contract_id: String) {
         assert_one_yocto();
         self.assert_only_owner();
+        emit_config_change("prev_governance_contract", &contract_id);
         self.prev_governance_contract = contract_id;
     }
     #[payable]
@@ -1008,6 +1639,7 @@ impl MetaVoteContract {
         assert_one_yocto();
         self.assert_only_owner();
         self.min_claim_and_bond_days = min_claim_and_lock_days;
+        emit_config_change("min_claim_and_bond_days", min_claim_and_lock_days);
     }
 
     // user-started migration of locking-positions from prev-governance-contract
@@ -1024,6 +1656,7 @@ impl MetaVoteContract {
             env::predecessor_account_id().to_string() == self.prev_governance_contract,
             "Only the old gov contract can call this function."
         );
+        self.internal_seed_vp_checkpoints(voter_id);
         let mut voter = self.internal_get_voter(&voter_id);
         // create locking positions
         for lp in &locking_positions {
@@ -1059,6 +1692,7 @@ impl MetaVoteContract {
         // example for an eth based address: f1552d1d7CD279A7B766F431c5FaC49A2fb6e361.evmp.near
         // evmp.near is controlled by the dao. No external user can create a xxx.evmp.near account
         let voter_id = utils::pseudo_near_address(&external_address);
+        self.internal_seed_vp_checkpoints(&voter_id);
         let mut voter = self.internal_get_voter(&voter_id);
 
         // sum vp in actual locking positions
@@ -1085,6 +1719,7 @@ impl MetaVoteContract {
 
         // also update contract total (new vp was already added, remove old only)
         self.total_voting_power = self.total_voting_power - prev_voting_power;
+        self.internal_after_vp_change(&voter_id, &mut voter);
 
         // save voter
         self.voters.insert(&voter_id, &voter);
@@ -1096,6 +1731,7 @@ impl MetaVoteContract {
     // adjust_voter_voting_power remove votes if needed
     pub fn operator_recompute_available_vp(&mut self, voter_id: &String) {
         self.assert_operator();
+        self.internal_seed_vp_checkpoints(voter_id);
         let mut voter = self.internal_get_voter(&voter_id);
 
         // recompute available and remove votes if needed
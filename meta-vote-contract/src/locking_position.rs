@@ -2,12 +2,31 @@ use crate::*;
 use near_sdk::json_types::U128;
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, Debug)]
+#[derive(BorshSerialize, Debug)]
 pub struct LockingPosition {
     pub amount: MpDAOAmount,
     pub locking_period: Days,
     pub voting_power: u128,
     pub unlocking_started_at: Option<EpochMillis>,
+    /// stable id, 0 for positions created before ids were added (no id assigned yet)
+    pub id: PositionId,
+}
+
+// positions stored before ids were added have no trailing id bytes
+impl BorshDeserialize for LockingPosition {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            amount: BorshDeserialize::deserialize(buf)?,
+            locking_period: BorshDeserialize::deserialize(buf)?,
+            voting_power: BorshDeserialize::deserialize(buf)?,
+            unlocking_started_at: BorshDeserialize::deserialize(buf)?,
+            id: if buf.is_empty() {
+                0
+            } else {
+                BorshDeserialize::deserialize(buf)?
+            },
+        })
+    }
 }
 
 impl LockingPosition {
@@ -16,6 +35,7 @@ impl LockingPosition {
     }
 
     pub(crate) fn new(
+        id: PositionId,
         amount: MpDAOAmount,
         locking_period: Days,
         voting_power: u128,
@@ -26,6 +46,7 @@ impl LockingPosition {
             locking_period,
             voting_power,
             unlocking_started_at,
+            id,
         }
     }
 
@@ -50,6 +71,7 @@ impl LockingPosition {
     pub(crate) fn to_json(&self, index: Option<PositionIndex>) -> LockingPositionJSON {
         LockingPositionJSON {
             index,
+            id: self.id,
             amount: U128::from(self.amount),
             locking_period: self.locking_period,
             voting_power: U128::from(self.voting_power),
@@ -62,6 +84,22 @@ impl LockingPosition {
 }
 
 impl MetaVoteContract {
+    pub(crate) fn internal_next_position_id(&mut self) -> PositionId {
+        let id = self.next_position_id;
+        self.next_position_id += 1;
+        id
+    }
+
+    /// positions created before ids were added have id 0, assign them an id when loaded
+    pub(crate) fn internal_assign_position_ids(&mut self, voter: &mut Voter) {
+        for index in 0..voter.locking_positions.len() {
+            let mut locking_position = voter.get_position(index);
+            if locking_position.id == 0 {
+                locking_position.id = self.internal_next_position_id();
+                voter.locking_positions.replace(index, &locking_position);
+            }
+        }
+    }
 
     fn increase_locking_position(
         &mut self,
@@ -70,7 +108,7 @@ impl MetaVoteContract {
         mpdao_amount: MpDAOAmount,
         unbond_days: Days,
     ) {
-        let voting_power = utils::calculate_voting_power(mpdao_amount, unbond_days);
+        let voting_power = self.internal_calculate_voting_power(mpdao_amount, unbond_days);
         let mut current_position = voter.get_position(index);
         current_position.amount += mpdao_amount;
         current_position.voting_power += voting_power;
@@ -93,40 +131,93 @@ impl MetaVoteContract {
         );
         // double-check it does not exists
         assert!(
-            voter.find_locked_position(unbond_days).is_none(),
+            self.internal_find_locked_position(voter, unbond_days)
+                .is_none(),
             "a locking-position for {} days already exists",
             unbond_days
         );
-        let voting_power = utils::calculate_voting_power(mpdao_amount, unbond_days);
-        let locking_position = LockingPosition::new(mpdao_amount, unbond_days, voting_power, None);
+        let voting_power = self.internal_calculate_voting_power(mpdao_amount, unbond_days);
+        let locking_position = LockingPosition::new(
+            self.internal_next_position_id(),
+            mpdao_amount,
+            unbond_days,
+            voting_power,
+            None,
+        );
         voter.locking_positions.push(&locking_position);
         voter.available_voting_power += voting_power;
         self.total_voting_power += voting_power;
     }
 
-    pub(crate) fn deposit_locking_position(
-        &mut self,
-        mpdao_amount: MpDAOAmount,
-        unbond_days: Days,
-        voter_id: &String,
-        voter: &mut Voter,
-    ) {
+    pub(crate) fn assert_unbond_period(&self, unbond_days: Days) {
         assert!(
             unbond_days >= self.min_unbond_period && unbond_days <= self.max_unbond_period,
             "Unbound period must be between {} and {} days",
             self.min_unbond_period,
             self.max_unbond_period
         );
+    }
+
+    /// the locked position for unbond_days, locked positions are unique by days.
+    /// NFT positions are not included, they cannot receive mpDAO.
+    pub(crate) fn internal_find_locked_position(
+        &self,
+        voter: &Voter,
+        unbond_days: Days,
+    ) -> Option<PositionIndex> {
+        voter
+            .locking_positions
+            .iter()
+            .position(|locking_position| {
+                locking_position.locking_period == unbond_days
+                    && locking_position.is_locked()
+                    && !self.is_nft_position(&locking_position)
+            })
+            .map(|index| index as PositionIndex)
+    }
 
-        match voter.find_locked_position(unbond_days) {
+    /// adds mpdao_amount to the locked position for unbond_days, or creates it.
+    /// Returns the position id
+    pub(crate) fn internal_lock_in_position(
+        &mut self,
+        voter: &mut Voter,
+        mpdao_amount: MpDAOAmount,
+        unbond_days: Days,
+    ) -> PositionId {
+        let index = match self.internal_find_locked_position(voter, unbond_days) {
             Some(index) => {
                 // Deposit into existing locking position.
                 self.increase_locking_position(voter, index, mpdao_amount, unbond_days);
+                index
             }
             None => {
                 self.internal_create_locking_position(voter, mpdao_amount, unbond_days);
+                voter.locking_positions.len() - 1
             }
         };
+        voter.get_position(index).id
+    }
+
+    pub(crate) fn deposit_locking_position(
+        &mut self,
+        mpdao_amount: MpDAOAmount,
+        unbond_days: Days,
+        voter_id: &String,
+        voter: &mut Voter,
+    ) {
+        self.assert_unbond_period(unbond_days);
+        self.internal_assign_position_ids(voter);
+        self.internal_lock_in_position(voter, mpdao_amount, unbond_days);
+        MetaVoteEvent::Lock(LockData {
+            account_id: voter_id,
+            amount: mpdao_amount.into(),
+            locking_period: unbond_days,
+            voting_power: self
+                .internal_calculate_voting_power(mpdao_amount, unbond_days)
+                .into(),
+        })
+        .emit();
+        self.internal_after_vp_change(voter_id, voter);
         self.voters.insert(&voter_id, &voter);
     }
 
@@ -146,6 +237,7 @@ impl MetaVoteContract {
             self.max_locking_positions
         );
         let unlocking_position = LockingPosition::new(
+            self.internal_next_position_id(),
             mpdao_amount,
             unbond_days,
             voting_power,
@@ -0,0 +1,217 @@
+use crate::*;
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+
+pub type DistributionId = u32;
+
+/// this struct can be sent in msg of ft_transfer_call ("for-merkle-claims:{...}")
+/// the sender deposits the total amount and the merkle root of all (index, account, amount) leaves
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MerkleClaimsInfo {
+    pub merkle_root: Base58CryptoHash,
+    pub bpu: u16, //0-10000 basis points to be distributed as unlocked
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MerkleDistribution {
+    pub token_contract: AccountId,
+    pub sender_id: AccountId,
+    pub merkle_root: CryptoHash,
+    pub bpu: u16,
+    pub total_amount: u128,
+    pub claimed_amount: u128,
+    pub created_at: EpochMillis,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MerkleDistributionJSON {
+    pub distribution_id: DistributionId,
+    pub token_contract: AccountId,
+    pub sender_id: AccountId,
+    pub merkle_root: Base58CryptoHash,
+    pub bpu: u16,
+    pub total_amount: U128,
+    pub claimed_amount: U128,
+    pub created_at: EpochMillis,
+}
+
+/// domain separation: an internal node cannot be presented as a leaf
+const MERKLE_LEAF_PREFIX: u8 = 0x00;
+const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+/// leaf = keccak256(0x00 ++ borsh(index: u64, account_id: String, amount: u128))
+pub fn merkle_leaf(index: u64, account_id: &String, amount: u128) -> CryptoHash {
+    let mut data = vec![MERKLE_LEAF_PREFIX];
+    data.extend(
+        (index, account_id, amount)
+            .try_to_vec()
+            .expect("leaf serialization"),
+    );
+    env::keccak256_array(&data)
+}
+
+/// sorted-pair merkle proof: node = keccak256(0x01 ++ min(a,b) ++ max(a,b))
+pub fn verify_merkle_proof(root: &CryptoHash, leaf: CryptoHash, proof: &[CryptoHash]) -> bool {
+    let mut computed = leaf;
+    for sibling in proof {
+        let mut data = Vec::with_capacity(65);
+        data.push(MERKLE_NODE_PREFIX);
+        if computed <= *sibling {
+            data.extend_from_slice(&computed);
+            data.extend_from_slice(sibling);
+        } else {
+            data.extend_from_slice(sibling);
+            data.extend_from_slice(&computed);
+        }
+        computed = env::keccak256_array(&data);
+    }
+    computed == *root
+}
+
+impl MetaVoteContract {
+    // called from ft_on_transfer
+    pub(crate) fn create_merkle_distribution(
+        &mut self,
+        sender_id: AccountId,
+        total_amount: u128,
+        info: MerkleClaimsInfo,
+    ) {
+        assert!(info.bpu <= 10000);
+        require!(total_amount > 0, "Nothing to distribute");
+        let token_address = env::predecessor_account_id();
+        if token_address == self.stnear_token_contract_address {
+            require!(
+                info.bpu == 10000,
+                "stNEAR cannot be distributed as locked yet, bpu must be 100%"
+            );
+        } else if token_address != self.mpdao_token_contract_address {
+            panic!("Unknown token address: {}", token_address);
+        }
+        let distribution_id = self.merkle_distributions.len() as DistributionId;
+        MetaVoteEvent::MerkleDistribution(DistributionData {
+            distribution_id: Some(distribution_id),
+            token_contract: token_address.as_str(),
+            total_amount: total_amount.into(),
+            bpu: info.bpu,
+        })
+        .emit();
+        self.merkle_distributions.push(&MerkleDistribution {
+            token_contract: token_address,
+            sender_id,
+            merkle_root: info.merkle_root.into(),
+            bpu: info.bpu,
+            total_amount,
+            claimed_amount: 0,
+            created_at: get_current_epoch_millis(),
+        });
+    }
+
+    /// returns the bitmap word key and the bit mask for a leaf index
+    fn merkle_claimed_bit(
+        distribution_id: DistributionId,
+        index: u64,
+    ) -> ((DistributionId, u64), u128) {
+        ((distribution_id, index / 128), 1_u128 << (index % 128))
+    }
+
+    fn is_merkle_leaf_claimed(&self, distribution_id: DistributionId, index: u64) -> bool {
+        let (key, mask) = Self::merkle_claimed_bit(distribution_id, index);
+        self.merkle_claimed_bitmap
+            .get(&key)
+            .map(|word| word & mask != 0)
+            .unwrap_or(false)
+    }
+
+    fn set_merkle_leaf_claimed(&mut self, distribution_id: DistributionId, index: u64) {
+        let (key, mask) = Self::merkle_claimed_bit(distribution_id, index);
+        let word = self.merkle_claimed_bitmap.get(&key).copied().unwrap_or(0);
+        self.merkle_claimed_bitmap.insert(key, word | mask);
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// Claim a leaf of a merkle distribution. Anyone can submit the proof,
+    /// the amount is always added to the account_id claimable balances.
+    pub fn claim_merkle_distribution(
+        &mut self,
+        distribution_id: DistributionId,
+        index: u64,
+        account_id: AccountId,
+        amount: U128String,
+        proof: Vec<Base58CryptoHash>,
+    ) {
+        let account_id = account_id.to_string();
+        let amount = amount.0;
+        require!(amount > 0, "Nothing to claim");
+        let mut distribution = self
+            .merkle_distributions
+            .get(distribution_id as u64)
+            .expect("Distribution not found");
+        require!(
+            !self.is_merkle_leaf_claimed(distribution_id, index),
+            "Already claimed"
+        );
+        let proof: Vec<CryptoHash> = proof.into_iter().map(|node| node.into()).collect();
+        require!(
+            verify_merkle_proof(
+                &distribution.merkle_root,
+                merkle_leaf(index, &account_id, amount),
+                &proof
+            ),
+            "Invalid merkle proof"
+        );
+        require!(
+            distribution.claimed_amount + amount <= distribution.total_amount,
+            "Claim exceeds distribution total"
+        );
+        self.set_merkle_leaf_claimed(distribution_id, index);
+        distribution.claimed_amount += amount;
+        self.merkle_distributions
+            .replace(distribution_id as u64, &distribution);
+
+        if distribution.token_contract == self.mpdao_token_contract_address {
+            self.add_claimable_mpdao_with_bpu(&account_id, amount, distribution.bpu);
+            self.accumulated_mpdao_distributed_for_claims += amount;
+        } else {
+            self.add_claimable_stnear(&account_id, amount);
+            self.accum_distributed_stnear_for_claims += amount;
+        }
+        MetaVoteEvent::MerkleClaim(MerkleClaimData {
+            distribution_id,
+            index,
+            account_id: &account_id,
+            token_contract: distribution.token_contract.as_str(),
+            amount: amount.into(),
+        })
+        .emit();
+    }
+
+    pub fn is_merkle_claimed(&self, distribution_id: DistributionId, index: u64) -> bool {
+        self.is_merkle_leaf_claimed(distribution_id, index)
+    }
+
+    pub fn get_merkle_distributions_count(&self) -> u32 {
+        self.merkle_distributions.len() as u32
+    }
+
+    pub fn get_merkle_distribution(
+        &self,
+        distribution_id: DistributionId,
+    ) -> Option<MerkleDistributionJSON> {
+        self.merkle_distributions
+            .get(distribution_id as u64)
+            .map(|distribution| MerkleDistributionJSON {
+                distribution_id,
+                token_contract: distribution.token_contract,
+                sender_id: distribution.sender_id,
+                merkle_root: distribution.merkle_root.into(),
+                bpu: distribution.bpu,
+                total_amount: distribution.total_amount.into(),
+                claimed_amount: distribution.claimed_amount.into(),
+                created_at: distribution.created_at,
+            })
+    }
+}
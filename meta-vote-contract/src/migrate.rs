@@ -52,6 +52,11 @@ pub struct OldState {
     // token info & mpdao_prices - added 2025-10-5
     pub token_info: UnorderedMap<AccountId, TokenInfo>,
     pub mpdao_prices: UnorderedMap<AccountId, MpdaoPrice>,
+
+    // MPDAO as unlocked rewards - added 2025-11-21
+    pub claimable_unlocked_mpdao: UnorderedMap<VoterId, u128>,
+    pub accumulated_unlocked_mpdao_distributed_for_claims: u128,
+    pub total_unclaimed_unlocked_mpdao: u128,
 }
 
 #[near_bindgen]
@@ -61,6 +66,21 @@ impl MetaVoteContract {
     pub fn migrate() -> Self {
         // retrieve the current state from the contract
         let old: OldState = env::state_read().expect("failed");
+
+        // first total vp checkpoint: current total, valid since forever
+        let mut total_vp_checkpoints = Vector::new(StorageKey::TotalVpCheckpoints);
+        total_vp_checkpoints.push(&VpCheckpoint {
+            timestamp: 0,
+            voting_power: old.total_voting_power,
+        });
+        // the validators & delegations policy that was hardcoded
+        let mut stale_policies = UnorderedMap::new(StorageKey::StalePolicies);
+        for (contract_address, policy) in default_stale_policies() {
+            stale_policies.insert(&contract_address, &policy);
+        }
+        // every existing voter has to be indexed in the voters per object index
+        let voters_count = old.voters.len();
+
         // return the new state
         Self {
             owner_id: old.owner_id,
@@ -109,11 +129,89 @@ impl MetaVoteContract {
             token_info: old.token_info,
             mpdao_prices: old.mpdao_prices,
 
-            // new in this version (2025-11-27)
-            // MPDAO as unlocked rewards (new storage)
-            claimable_unlocked_mpdao: UnorderedMap::new(StorageKey::ClaimableUnlockedMpdao),
-            accumulated_unlocked_mpdao_distributed_for_claims: 0,
-            total_unclaimed_unlocked_mpdao: 0,
+            claimable_unlocked_mpdao: old.claimable_unlocked_mpdao,
+            accumulated_unlocked_mpdao_distributed_for_claims: old
+                .accumulated_unlocked_mpdao_distributed_for_claims,
+            total_unclaimed_unlocked_mpdao: old.total_unclaimed_unlocked_mpdao,
+
+            // new in this version (2026-10)
+            // voting power checkpoints for snapshot voting
+            vp_checkpoints: UnorderedMap::new(StorageKey::VpCheckpoints),
+            total_vp_checkpoints,
+
+            // merkle-root based distributions for claims
+            merkle_distributions: Vector::new(StorageKey::MerkleDistributions),
+            merkle_claimed_bitmap: LookupMap::new(StorageKey::MerkleClaimedBitmap),
+
+            // stable locking position ids & locking position NFTs
+            next_position_id: 1,
+            position_nfts: UnorderedMap::new(StorageKey::PositionNfts),
+            position_nft_token_ids: LookupMap::new(StorageKey::PositionNftTokenIds),
+
+            // empty curve: the linear voting power multiplier
+            vp_curve: Vec::new(),
+            mpip_contract_id: None,
+
+            // early exit disabled until configured
+            early_exit_config: None,
+            early_exit_penalty_pool: 0,
+
+            vote_allocations_bp: LookupMap::new(StorageKey::VoteAllocationsBp),
+
+            // the registry is not enforced until the apps are registered
+            apps: UnorderedMap::new(StorageKey::Apps),
+            app_objects: UnorderedMap::new(StorageKey::AppObjectLists),
+            app_registry_enforced: false,
+
+            voting_rounds: Vector::new(StorageKey::VotingRounds),
+            active_voting_rounds: LookupMap::new(StorageKey::ActiveVotingRounds),
+            round_voter_votes: LookupMap::new(StorageKey::RoundVoterVotes),
+
+            stale_policies,
+
+            stale_sweep_cursor: 0,
+            stale_sweep_bounty: 0,
+            stale_sweep_pool: 0,
+
+            app_vote_listeners: LookupMap::new(StorageKey::AppVoteListeners),
+
+            // existing votes are indexed by operator_index_object_voters
+            object_voters: LookupMap::new(StorageKey::ObjectVoterLists),
+            object_voters_backfill_index: voters_count,
+
+            delegations_bp: LookupMap::new(StorageKey::DelegationsBp),
+
+            delegation_expiries: LookupMap::new(StorageKey::DelegationExpiries),
+            delegation_expiry_queue: TreeMap::new(StorageKey::DelegationExpiryQueue),
+
+            // not enforced until the delegates are registered
+            delegate_profiles: UnorderedMap::new(StorageKey::DelegateProfiles),
+            delegate_registry_enforced: false,
+            delegates_by_vp: TreeMap::new(StorageKey::DelegatesByVp),
+
+            // existing delegations have the All scope, voters are read with no scoped delegations
+            delegation_scopes: LookupMap::new(StorageKey::DelegationScopes),
+
+            // signatures of existing delegations used nonce 0
+            evm_delegation_nonces: LookupMap::new(StorageKey::EvmDelegationNonces),
+        }
+    }
+
+    /// after migration: assigns a stable id to existing locking positions created before ids.
+    /// Positions also get an id when their voter is loaded by a position API.
+    /// Walks the voters backwards, from `to_index` (exclusive) down, because removing a voter
+    /// moves the last voter into its place: voters below `to_index` are never moved up.
+    /// Returns the index to continue from, 0 when all voters were processed.
+    pub fn operator_assign_position_ids(&mut self, to_index: u32, limit: u32) -> u32 {
+        self.assert_operator();
+        let keys = self.voters.keys_as_vector();
+        let end = std::cmp::min(to_index as u64, keys.len());
+        let start = end.saturating_sub(limit as u64);
+        let voter_ids: Vec<String> = (start..end).map(|index| keys.get(index).unwrap()).collect();
+        for voter_id in voter_ids {
+            let mut voter = self.voters.get(&voter_id).unwrap();
+            self.internal_assign_position_ids(&mut voter);
         }
+        start as u32
     }
 }
@@ -0,0 +1,381 @@
+use crate::*;
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+use near_contract_standards::non_fungible_token::events::{NftBurn, NftMint, NftTransfer};
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
+};
+use near_contract_standards::non_fungible_token::Token;
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, serde_json, PromiseResult};
+use std::collections::HashMap;
+
+/// A locking position exposed as a NEP-171 NFT.
+/// The position itself stays in the owner's `Voter.locking_positions`,
+/// transferring the NFT moves the position to the receiver.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct NftPosition {
+    pub owner_id: VoterId,
+    pub position_id: PositionId,
+}
+
+#[allow(dead_code)]
+#[ext_contract(ext_nft_receiver)]
+pub trait NonFungibleTokenReceiver {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+impl MetaVoteContract {
+    pub(crate) fn is_nft_position(&self, locking_position: &LockingPosition) -> bool {
+        locking_position.id != 0
+            && self
+                .position_nft_token_ids
+                .get(&locking_position.id)
+                .is_some()
+    }
+
+    pub(crate) fn assert_not_nft_position(&self, locking_position: &LockingPosition) {
+        require!(
+            !self.is_nft_position(locking_position),
+            "The locking position is a NFT, burn the NFT first"
+        );
+    }
+
+    /// keeps locked positions unique by days when a NFT position becomes a regular one:
+    /// merges it into the regular locked position with the same days, if any
+    fn internal_merge_same_days_position(&mut self, voter_id: &VoterId, position_id: PositionId) {
+        let mut voter = self.internal_get_voter_or_panic(voter_id);
+        let index = voter.get_position_index(position_id);
+        let locking_position = voter.get_position(index);
+        let target_index = match self
+            .internal_find_locked_position(&voter, locking_position.locking_period)
+            .filter(|target_index| *target_index != index)
+        {
+            Some(target_index) => target_index,
+            None => return,
+        };
+        // same amount & voting power for the voter, only the positions change
+        let mut target = voter.get_position(target_index);
+        target.amount += locking_position.amount;
+        target.voting_power += locking_position.voting_power;
+        voter.locking_positions.replace(target_index, &target);
+        voter.remove_position(index);
+        self.voters.insert(voter_id, &voter);
+        MetaVoteEvent::Merge(MergeData {
+            account_id: voter_id,
+            position_ids: &[position_id, target.id],
+            position_id: target.id,
+            locking_period: target.locking_period,
+            voting_power: target.voting_power.into(),
+        })
+        .emit();
+    }
+
+    /// returns the owner voter, the position index and the position of a NFT
+    fn internal_get_nft_position(
+        &self,
+        nft: &NftPosition,
+    ) -> (Voter, PositionIndex, LockingPosition) {
+        let voter = self.internal_get_voter_or_panic(&nft.owner_id);
+        let index = voter
+            .find_position_index_by_id(nft.position_id)
+            .expect("NFT locking position not found");
+        let locking_position = voter.get_position(index);
+        (voter, index, locking_position)
+    }
+
+    fn internal_nft_token(&self, token_id: TokenId) -> Option<Token> {
+        let nft = self.position_nfts.get(&token_id)?;
+        let (_, _, locking_position) = self.internal_get_nft_position(&nft);
+        Some(Token {
+            token_id,
+            owner_id: AccountId::new_unchecked(nft.owner_id),
+            metadata: Some(TokenMetadata {
+                title: Some(format!(
+                    "{} mpDAO locked for {} days",
+                    locking_position.amount / ONE_MPDAO,
+                    locking_position.locking_period
+                )),
+                description: None,
+                media: None,
+                media_hash: None,
+                copies: None,
+                issued_at: None,
+                expires_at: None,
+                starts_at: None,
+                updated_at: None,
+                extra: serde_json::to_string(&locking_position.to_json(None)).ok(),
+                reference: None,
+                reference_hash: None,
+            }),
+            approved_account_ids: None,
+        })
+    }
+
+    /// moves the NFT locking position, and its voting power, from the owner to receiver_id.
+    /// The previous owner votes are trimmed if they exceed the remaining voting power.
+    fn internal_nft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        memo: Option<&str>,
+    ) {
+        let mut nft = self.position_nfts.get(token_id).expect("Token not found");
+        require!(
+            nft.owner_id == sender_id.as_str(),
+            "Sender is not the owner"
+        );
+        require!(
+            sender_id != receiver_id,
+            "Current and next owner must differ"
+        );
+        // mirrored evm addresses positions are managed by the operator
+        require!(
+            !receiver_id.as_str().ends_with(".evmp.near"),
+            "Cannot transfer to a mirrored evm address"
+        );
+        self.internal_seed_vp_checkpoints(&nft.owner_id);
+        let (mut owner, index, locking_position) = self.internal_get_nft_position(&nft);
+        require!(
+            locking_position.is_locked(),
+            "Only locked positions can be transferred"
+        );
+
+        // remove the position from the previous owner, trim votes if needed
+        owner.remove_position(index);
+        self.adjust_voter_voting_power(&nft.owner_id, &mut owner);
+        self.internal_after_vp_change(&nft.owner_id, &mut owner);
+        self.voters.insert(&nft.owner_id, &owner);
+
+        // add the position to the receiver. total_voting_power does not change
+        let receiver_voter_id = receiver_id.to_string();
+        self.internal_seed_vp_checkpoints(&receiver_voter_id);
+        let mut receiver = self.internal_get_voter(&receiver_voter_id);
+        assert!(
+            (receiver.locking_positions.len() as u8) < self.max_locking_positions,
+            "The max number of locking positions is {}",
+            self.max_locking_positions
+        );
+        receiver.available_voting_power += locking_position.voting_power;
+        receiver.locking_positions.push(&locking_position);
+        self.internal_after_vp_change(&receiver_voter_id, &mut receiver);
+        self.voters.insert(&receiver_voter_id, &receiver);
+
+        nft.owner_id = receiver_voter_id;
+        self.position_nfts.insert(token_id, &nft);
+
+        NftTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            token_ids: &[token_id],
+            authorized_id: None,
+            memo,
+        }
+        .emit();
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // ****************************
+    // * Locking position NFTs    *
+    // ****************************
+
+    /// Expose a locked position as a NEP-171 NFT, token id is "{voter_id}:{position_id}"
+    #[payable]
+    pub fn nft_mint_locking_position(&mut self, position_id: PositionId) -> TokenId {
+        assert_one_yocto();
+        let voter_id = env::predecessor_account_id();
+        let mut voter = self.internal_get_voter_or_panic(&voter_id.to_string());
+        self.internal_assign_position_ids(&mut voter);
+        let locking_position = voter.get_position(voter.get_position_index(position_id));
+        require!(
+            locking_position.is_locked(),
+            "Only locked positions can be NFTs"
+        );
+        self.assert_not_nft_position(&locking_position);
+
+        let token_id = format!("{}:{}", voter_id, locking_position.id);
+        self.position_nfts.insert(
+            &token_id,
+            &NftPosition {
+                owner_id: voter_id.to_string(),
+                position_id: locking_position.id,
+            },
+        );
+        self.position_nft_token_ids
+            .insert(locking_position.id, token_id.clone());
+
+        NftMint {
+            owner_id: &voter_id,
+            token_ids: &[&token_id],
+            memo: None,
+        }
+        .emit();
+        token_id
+    }
+
+    /// Removes the NFT, the position remains as a regular locking position of the owner.
+    /// If the owner has a regular locked position with the same days, it is merged into it.
+    #[payable]
+    pub fn nft_burn_locking_position(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let nft = self.position_nfts.get(&token_id).expect("Token not found");
+        require!(nft.owner_id == owner_id.as_str(), "Sender is not the owner");
+        self.position_nfts.remove(&token_id);
+        self.position_nft_token_ids.remove(&nft.position_id);
+        self.internal_merge_same_days_position(&nft.owner_id, nft.position_id);
+
+        NftBurn {
+            owner_id: &owner_id,
+            token_ids: &[&token_id],
+            authorized_id: None,
+            memo: None,
+        }
+        .emit();
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenCore for MetaVoteContract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        require!(approval_id.is_none(), "Approvals are not supported");
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(&sender_id, &receiver_id, &token_id, memo.as_deref());
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        require!(approval_id.is_none(), "Approvals are not supported");
+        let sender_id = env::predecessor_account_id();
+        self.internal_nft_transfer(&sender_id, &receiver_id, &token_id, memo.as_deref());
+        ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_NFT_ON_TRANSFER)
+            .nft_on_transfer(sender_id.clone(), sender_id.clone(), token_id.clone(), msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_NFT_TRANSFER)
+                    .nft_resolve_transfer(sender_id, receiver_id, token_id, None),
+            )
+            .into()
+    }
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.internal_nft_token(token_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for MetaVoteContract {
+    /// returns true if the token was successfully transferred to receiver_id
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        #[allow(unused_variables)] approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+        if !must_revert {
+            return true;
+        }
+        // return the position only if the receiver still owns it and it is still locked
+        if let Some(nft) = self.position_nfts.get(&token_id) {
+            if nft.owner_id == receiver_id.as_str() {
+                let (_, _, locking_position) = self.internal_get_nft_position(&nft);
+                if locking_position.is_locked() {
+                    self.internal_nft_transfer(&receiver_id, &previous_owner_id, &token_id, None);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenEnumeration for MetaVoteContract {
+    fn nft_total_supply(&self) -> U128 {
+        (self.position_nfts.len() as u128).into()
+    }
+
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        let keys = self.position_nfts.keys_as_vector();
+        let start = from_index.map(|index| index.0 as u64).unwrap_or(0);
+        let end = std::cmp::min(start + limit.unwrap_or(keys.len()), keys.len());
+        (start..end)
+            .filter_map(|index| self.internal_nft_token(keys.get(index).unwrap()))
+            .collect()
+    }
+
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        (self.nft_tokens_for_owner(account_id, None, None).len() as u128).into()
+    }
+
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let voter = self.internal_get_voter(&account_id.to_string());
+        let start = from_index.map(|index| index.0 as usize).unwrap_or(0);
+        voter
+            .locking_positions
+            .iter()
+            .filter(|locking_position| locking_position.id != 0)
+            .filter_map(|locking_position| self.position_nft_token_ids.get(&locking_position.id))
+            .skip(start)
+            .take(limit.unwrap_or(u64::MAX) as usize)
+            .filter_map(|token_id| self.internal_nft_token(token_id.clone()))
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for MetaVoteContract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "mpDAO Locking Positions".to_string(),
+            symbol: "mpDAO-LP".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
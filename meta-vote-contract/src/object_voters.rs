@@ -0,0 +1,121 @@
+use crate::*;
+
+impl MetaVoteContract {
+    fn object_voters_key(
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) -> CryptoHash {
+        generate_hash_id(&format!("{}:{}", contract_address, votable_object_id))
+    }
+
+    pub(crate) fn internal_get_object_voters(
+        &self,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+    ) -> Option<&UnorderedMap<VoterId, u128>> {
+        self.object_voters.get(&Self::object_voters_key(
+            contract_address,
+            votable_object_id,
+        ))
+    }
+
+    /// keeps the voters per object index, call it after a vote changed. 0 removes the voter.
+    pub(crate) fn internal_index_object_voter(
+        &mut self,
+        voter_id: &String,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+        voting_power: u128,
+    ) {
+        let hash_id = Self::object_voters_key(contract_address, votable_object_id);
+        let mut voters = self
+            .object_voters
+            .remove(&hash_id)
+            .unwrap_or(UnorderedMap::new(StorageKey::ObjectVoters { hash_id }));
+        if voting_power == 0 {
+            voters.remove(voter_id);
+        } else {
+            voters.insert(voter_id, &voting_power);
+        }
+        if !voters.is_empty() {
+            self.object_voters.insert(hash_id, voters);
+        }
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// who voted for an object, with their votes and last vote timestamp
+    pub fn get_voters_for_object(
+        &self,
+        contract_address: ContractAddress,
+        votable_object_id: VotableObjId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<ObjectVoterJSON> {
+        let voters = match self.internal_get_object_voters(&contract_address, &votable_object_id) {
+            Some(voters) => voters,
+            None => return Vec::new(),
+        };
+        let keys = voters.keys_as_vector();
+        let values = voters.values_as_vector();
+        let start = from_index as u64;
+        let end = std::cmp::min(start + limit as u64, keys.len());
+        (start..end)
+            .map(|index| {
+                let voter_id = keys.get(index).unwrap();
+                ObjectVoterJSON {
+                    vote_timestamp: self.get_vote_timestamp(
+                        &voter_id,
+                        &contract_address,
+                        &votable_object_id,
+                    ),
+                    voter_id,
+                    voting_power: values.get(index).unwrap().into(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_voters_for_object_count(
+        &self,
+        contract_address: ContractAddress,
+        votable_object_id: VotableObjId,
+    ) -> u64 {
+        self.internal_get_object_voters(&contract_address, &votable_object_id)
+            .map(|voters| voters.len())
+            .unwrap_or(0)
+    }
+
+    /// after migration: indexes the votes cast before the voters per object index, in batches
+    /// of `limit` voters. Walks the voters backwards from the last one not indexed, because
+    /// removing a voter moves the last voter into its place. Returns the voters left to index.
+    pub fn operator_index_object_voters(&mut self, limit: u32) -> u64 {
+        self.assert_operator();
+        let keys = self.voters.keys_as_vector();
+        let end = std::cmp::min(self.object_voters_backfill_index, keys.len());
+        let start = end.saturating_sub(limit as u64);
+        let voter_ids: Vec<String> = (start..end).map(|index| keys.get(index).unwrap()).collect();
+        for voter_id in voter_ids {
+            let voter = self.voters.get(&voter_id).unwrap();
+            for contract_address in voter.vote_positions.keys_as_vector().iter() {
+                let votes_for_address = voter.vote_positions.get(&contract_address).unwrap();
+                for (votable_object_id, voting_power) in votes_for_address.iter() {
+                    self.internal_index_object_voter(
+                        &voter_id,
+                        &contract_address,
+                        &votable_object_id,
+                        voting_power,
+                    );
+                }
+            }
+        }
+        self.object_voters_backfill_index = start;
+        start
+    }
+
+    /// true when the votes cast before the upgrade are in the voters per object index
+    pub fn is_object_voters_index_complete(&self) -> bool {
+        self.object_voters_backfill_index == 0
+    }
+}
@@ -0,0 +1,327 @@
+use crate::*;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+
+pub type RoundId = u32;
+
+/// A voting round of an app (contract_address).
+/// During the freeze window (the last freeze_ms before end) votes cannot be removed or decreased.
+/// After end, the app votes are frozen until the snapshot of the votes is finalized.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VotingRound {
+    pub contract_address: ContractAddress,
+    pub name: String,
+    pub start_ms: EpochMillis,
+    pub end_ms: EpochMillis,
+    pub freeze_ms: u64,
+    pub finalized: bool,
+    /// snapshot progress, see finalize_voting_round
+    pub objects_snapshot_index: u64,
+    /// voters of the object at objects_snapshot_index already in the snapshot
+    pub voters_snapshot_index: u64,
+    /// total votes per object at the end of the round
+    pub results: Vector<(VotableObjId, u128)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VotingRoundJSON {
+    pub round_id: RoundId,
+    pub contract_address: ContractAddress,
+    pub name: String,
+    pub start_ms: EpochMillis,
+    pub end_ms: EpochMillis,
+    pub freeze_ms: u64,
+    pub finalized: bool,
+    pub results_count: u64,
+}
+
+impl VotingRound {
+    fn to_json(&self, round_id: RoundId) -> VotingRoundJSON {
+        VotingRoundJSON {
+            round_id,
+            contract_address: self.contract_address.clone(),
+            name: self.name.clone(),
+            start_ms: self.start_ms,
+            end_ms: self.end_ms,
+            freeze_ms: self.freeze_ms,
+            finalized: self.finalized,
+            results_count: self.results.len(),
+        }
+    }
+
+    fn is_frozen(&self, now: EpochMillis) -> bool {
+        !self.finalized && now + self.freeze_ms >= self.end_ms
+    }
+
+    fn has_ended(&self, now: EpochMillis) -> bool {
+        !self.finalized && now >= self.end_ms
+    }
+}
+
+impl MetaVoteContract {
+    fn internal_get_active_round(&self, contract_address: &ContractAddress) -> Option<VotingRound> {
+        self.active_voting_rounds
+            .get(contract_address)
+            .map(|round_id| self.voting_rounds.get(*round_id as u64).unwrap())
+    }
+
+    /// votes cannot be removed or decreased: freeze window or ended round not finalized yet
+    pub(crate) fn is_round_frozen(&self, contract_address: &ContractAddress) -> bool {
+        self.internal_get_active_round(contract_address)
+            .map(|round| round.is_frozen(env::block_timestamp_ms()))
+            .unwrap_or(false)
+    }
+
+    /// the round ended, no vote changes until the snapshot is finalized
+    pub(crate) fn is_round_ended(&self, contract_address: &ContractAddress) -> bool {
+        self.internal_get_active_round(contract_address)
+            .map(|round| round.has_ended(env::block_timestamp_ms()))
+            .unwrap_or(false)
+    }
+
+    /// adds the votes of the voters of an object to their round snapshot, up to `remaining`
+    /// voters. Returns true when all the object voters are in the snapshot.
+    fn internal_snapshot_object_voters(
+        &mut self,
+        round_id: RoundId,
+        round: &mut VotingRound,
+        votable_object_id: &VotableObjId,
+        remaining: &mut u64,
+    ) -> bool {
+        let object_voters =
+            match self.internal_get_object_voters(&round.contract_address, votable_object_id) {
+                Some(object_voters) => object_voters,
+                None => return true,
+            };
+        let keys = object_voters.keys_as_vector();
+        let values = object_voters.values_as_vector();
+        let end = std::cmp::min(round.voters_snapshot_index + *remaining, keys.len());
+        let voters: Vec<(VoterId, u128)> = (round.voters_snapshot_index..end)
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect();
+        let voters_count = keys.len();
+        for (voter_id, voting_power) in voters {
+            let key = (round_id, voter_id);
+            let mut voter_votes = self.round_voter_votes.remove(&key).unwrap_or_default();
+            voter_votes.push((votable_object_id.clone(), voting_power));
+            self.round_voter_votes.insert(key, voter_votes);
+            round.voters_snapshot_index += 1;
+            *remaining -= 1;
+        }
+        round.voters_snapshot_index >= voters_count
+    }
+
+    /// the voter snapshots come from the voters per object index
+    fn assert_object_voters_index_complete(&self) {
+        require!(
+            self.object_voters_backfill_index == 0,
+            "The voters per object index is not complete, run operator_index_object_voters."
+        );
+    }
+
+    /// the operator, the owner or the app owner (if the app is registered)
+    fn assert_can_manage_rounds(&self, contract_address: &ContractAddress) {
+        let predecessor = env::predecessor_account_id();
+        let is_app_owner = self
+            .apps
+            .get(contract_address)
+            .map(|app| app.owner_id == predecessor)
+            .unwrap_or(false);
+        require!(
+            is_app_owner || self.operator_id == predecessor || self.owner_id == predecessor,
+            "Only the operator, the owner or the app owner can manage rounds."
+        );
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // *****************
+    // * Voting rounds *
+    // *****************
+
+    /// One round per app at a time, rounds of different apps can run concurrently
+    pub fn create_voting_round(
+        &mut self,
+        contract_address: ContractAddress,
+        name: String,
+        start_ms: EpochMillis,
+        end_ms: EpochMillis,
+        freeze_ms: u64,
+    ) -> RoundId {
+        self.assert_can_manage_rounds(&contract_address);
+        require!(
+            contract_address != DELEGATED_CONTRACT_CODE,
+            "Reserved contract address."
+        );
+        self.assert_object_voters_index_complete();
+        require!(
+            self.active_voting_rounds.get(&contract_address).is_none(),
+            "The app has a round not finalized yet."
+        );
+        require!(start_ms < end_ms, "start_ms must be before end_ms.");
+        require!(
+            end_ms > env::block_timestamp_ms(),
+            "end_ms must be in the future."
+        );
+        require!(
+            freeze_ms <= end_ms - start_ms,
+            "The freeze window cannot be longer than the round."
+        );
+        let round_id = self.voting_rounds.len() as RoundId;
+        self.voting_rounds.push(&VotingRound {
+            contract_address: contract_address.clone(),
+            name,
+            start_ms,
+            end_ms,
+            freeze_ms,
+            finalized: false,
+            objects_snapshot_index: 0,
+            voters_snapshot_index: 0,
+            results: Vector::new(StorageKey::RoundResults { round_id }),
+        });
+        self.active_voting_rounds
+            .insert(contract_address.clone(), round_id);
+        MetaVoteEvent::CreateRound(RoundData {
+            round_id,
+            contract_address: &contract_address,
+            start_ms,
+            end_ms,
+            freeze_ms,
+        })
+        .emit();
+        round_id
+    }
+
+    /// After the end, anyone can take the snapshot of the votes, in batches of `limit`
+    /// app objects and voters. Returns true when the round is finalized.
+    pub fn finalize_voting_round(&mut self, round_id: RoundId, limit: u32) -> bool {
+        let mut round = self
+            .voting_rounds
+            .get(round_id as u64)
+            .expect("Round not found.");
+        require!(!round.finalized, "The round is already finalized.");
+        self.assert_object_voters_index_complete();
+        require!(
+            env::block_timestamp_ms() >= round.end_ms,
+            "The round has not ended."
+        );
+        let mut remaining = limit as u64;
+
+        // total votes per object and votes per voter, from the voters of each object.
+        // The app votes cannot change after the end, so both indexes are stable.
+        let objects_count = match self.votes.get(&round.contract_address) {
+            Some(votes_for_address) => {
+                let keys = votes_for_address.keys_as_vector();
+                let values = votes_for_address.values_as_vector();
+                while round.objects_snapshot_index < keys.len() && remaining > 0 {
+                    let votable_object_id = keys.get(round.objects_snapshot_index).unwrap();
+                    let voters_done = self.internal_snapshot_object_voters(
+                        round_id,
+                        &mut round,
+                        &votable_object_id,
+                        &mut remaining,
+                    );
+                    if voters_done {
+                        let votes = values.get(round.objects_snapshot_index).unwrap();
+                        if votes > 0 {
+                            round.results.push(&(votable_object_id, votes));
+                        }
+                        round.objects_snapshot_index += 1;
+                        round.voters_snapshot_index = 0;
+                        remaining = remaining.saturating_sub(1);
+                    }
+                }
+                keys.len()
+            }
+            None => 0,
+        };
+
+        if round.objects_snapshot_index >= objects_count {
+            round.finalized = true;
+            self.active_voting_rounds.remove(&round.contract_address);
+            MetaVoteEvent::FinalizeRound(RoundData {
+                round_id,
+                contract_address: &round.contract_address,
+                start_ms: round.start_ms,
+                end_ms: round.end_ms,
+                freeze_ms: round.freeze_ms,
+            })
+            .emit();
+        }
+        self.voting_rounds.replace(round_id as u64, &round);
+        round.finalized
+    }
+
+    pub fn get_voting_rounds_count(&self) -> u32 {
+        self.voting_rounds.len() as u32
+    }
+
+    pub fn get_voting_round(&self, round_id: RoundId) -> Option<VotingRoundJSON> {
+        self.voting_rounds
+            .get(round_id as u64)
+            .map(|round| round.to_json(round_id))
+    }
+
+    pub fn get_voting_rounds(&self, from_index: u32, limit: u32) -> Vec<VotingRoundJSON> {
+        let start = from_index as u64;
+        let end = std::cmp::min(start + limit as u64, self.voting_rounds.len());
+        (start..end)
+            .map(|index| {
+                self.voting_rounds
+                    .get(index)
+                    .unwrap()
+                    .to_json(index as RoundId)
+            })
+            .collect()
+    }
+
+    /// the round of the app not finalized yet
+    pub fn get_active_voting_round(
+        &self,
+        contract_address: ContractAddress,
+    ) -> Option<VotingRoundJSON> {
+        self.active_voting_rounds
+            .get(&contract_address)
+            .and_then(|round_id| self.get_voting_round(*round_id))
+    }
+
+    /// total votes per object at the end of the round, only objects with votes
+    pub fn get_voting_round_results(
+        &self,
+        round_id: RoundId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<(VotableObjId, U128)> {
+        let round = self
+            .voting_rounds
+            .get(round_id as u64)
+            .expect("Round not found.");
+        let start = from_index as u64;
+        let end = std::cmp::min(start + limit as u64, round.results.len());
+        (start..end)
+            .map(|index| {
+                let (votable_object_id, votes) = round.results.get(index).unwrap();
+                (votable_object_id, votes.into())
+            })
+            .collect()
+    }
+
+    /// votes of a voter in the app at the end of the round
+    pub fn get_voting_round_voter_votes(
+        &self,
+        round_id: RoundId,
+        voter_id: VoterId,
+    ) -> Vec<(VotableObjId, U128)> {
+        self.round_voter_votes
+            .get(&(round_id, voter_id))
+            .map(|votes| {
+                votes
+                    .iter()
+                    .map(|(votable_object_id, votes)| (votable_object_id.clone(), (*votes).into()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,114 @@
+use crate::timestamp_utils::{SEVEN_DAYS_MS, SIXTY_DAYS_MS};
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// Vote freshness rules of a votable app (contract_address)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StalePolicy {
+    /// if false votes never get stale and cannot be refreshed
+    pub stale_enabled: bool,
+    /// a vote not refreshed for this time is stale and can be removed
+    pub stale_after_ms: u64,
+    /// yoctoNEAR to attach to refresh_vote_timestamps
+    pub refresh_fee: U128String,
+    /// time since the vote (or last refresh) before the voter can unvote
+    pub min_hold_ms: u64,
+}
+
+/// policies before the table existed: validators and delegations get stale after 60 days
+pub(crate) fn default_stale_policies() -> Vec<(ContractAddress, StalePolicy)> {
+    let policy = |min_hold_ms| StalePolicy {
+        stale_enabled: true,
+        stale_after_ms: SIXTY_DAYS_MS,
+        refresh_fee: (ONE_NEAR / 100).into(),
+        min_hold_ms,
+    };
+    vec![
+        ("metastaking.app".into(), policy(0)),
+        (DELEGATED_CONTRACT_CODE.into(), policy(SEVEN_DAYS_MS)),
+    ]
+}
+
+impl MetaVoteContract {
+    /// the app policy if staleness applies to it
+    pub(crate) fn internal_get_stale_policy(
+        &self,
+        contract_address: &ContractAddress,
+    ) -> Option<StalePolicy> {
+        self.stale_policies
+            .get(contract_address)
+            .filter(|policy| policy.stale_enabled)
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // ****************
+    // * Stale policy *
+    // ****************
+
+    /// None removes the policy: votes of the app never get stale
+    #[payable]
+    pub fn set_stale_policy(
+        &mut self,
+        contract_address: ContractAddress,
+        policy: Option<StalePolicy>,
+    ) {
+        assert_one_yocto();
+        self.assert_only_owner();
+        emit_config_change(
+            "stale_policy",
+            format!(
+                "{} {}",
+                contract_address,
+                near_sdk::serde_json::to_string(&policy).expect("policy serialization")
+            ),
+        );
+        match policy {
+            Some(policy) => {
+                require!(policy.stale_after_ms > 0, "stale_after_ms cannot be 0");
+                self.stale_policies.insert(&contract_address, &policy);
+            }
+            None => {
+                self.stale_policies.remove(&contract_address);
+            }
+        }
+    }
+
+    pub fn get_stale_policy(&self, contract_address: ContractAddress) -> Option<StalePolicy> {
+        self.stale_policies.get(&contract_address)
+    }
+
+    pub fn get_stale_policies(
+        &self,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<(ContractAddress, StalePolicy)> {
+        self.stale_policies
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// the deposit to attach to refresh_vote_timestamps for this voter
+    pub fn get_refresh_fee(&self, voter_id: VoterId) -> U128String {
+        self.voters
+            .get(&voter_id)
+            .map(|voter| {
+                voter
+                    .vote_positions
+                    .keys_as_vector()
+                    .iter()
+                    .filter_map(|contract_address| {
+                        self.internal_get_stale_policy(&contract_address)
+                    })
+                    .map(|policy| policy.refresh_fee.0)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0)
+            .into()
+    }
+}
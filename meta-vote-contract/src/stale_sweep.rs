@@ -0,0 +1,119 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StaleSweepInfo {
+    pub cursor: u64,
+    pub voters_count: u64,
+    pub bounty_per_vote: U128String,
+    pub pool: U128String,
+}
+
+impl MetaVoteContract {
+    fn has_gas_for_sweep(&self) -> bool {
+        env::used_gas() + GAS_RESERVE_FOR_STALE_SWEEP < env::prepaid_gas()
+    }
+
+    /// removes the stale votes of a voter while there is gas.
+    /// Returns the removed count and if the voter was completed.
+    fn internal_sweep_voter(&mut self, voter_id: &String) -> (u32, bool) {
+        let voter = match self.voters.get(voter_id) {
+            Some(voter) => voter,
+            None => return (0, true),
+        };
+        let mut stale_votes = Vec::new();
+        for contract_address in voter.vote_positions.keys_as_vector().iter() {
+            if self.internal_get_stale_policy(&contract_address).is_none() {
+                continue;
+            }
+            let votes_for_address = voter.vote_positions.get(&contract_address).unwrap();
+            for votable_object_id in votes_for_address.keys_as_vector().iter() {
+                if self.verify_vote_is_stale(voter_id, &contract_address, &votable_object_id)
+                    && !self.are_votes_locked(&contract_address, &votable_object_id)
+                {
+                    stale_votes.push((contract_address.clone(), votable_object_id));
+                }
+            }
+        }
+        let mut removed = 0;
+        for (contract_address, votable_object_id) in stale_votes.iter() {
+            if !self.has_gas_for_sweep() {
+                return (removed, false);
+            }
+            self.internal_remove_stale_vote(voter_id, contract_address, votable_object_id);
+            removed += 1;
+        }
+        (removed, true)
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    // ************************
+    // * Stale vote sweeping *
+    // ************************
+
+    /// Permissionless: checks up to max_voters voters from the sweep cursor, removing their
+    /// stale votes while there is gas. The caller gets stale_sweep_bounty per removed vote,
+    /// paid from the pool of refresh fees. The cursor wraps around at the end of the voters.
+    /// Returns the removed votes count.
+    pub fn sweep_stale_votes(&mut self, max_voters: u32) -> u32 {
+        let voters_count = self.voters.len();
+        if self.stale_sweep_cursor >= voters_count {
+            self.stale_sweep_cursor = 0;
+        }
+        let mut removed = 0;
+        for _ in 0..std::cmp::min(max_voters as u64, voters_count) {
+            if !self.has_gas_for_sweep() {
+                break;
+            }
+            let voter_id = self
+                .voters
+                .keys_as_vector()
+                .get(self.stale_sweep_cursor)
+                .unwrap();
+            let (count, completed) = self.internal_sweep_voter(&voter_id);
+            removed += count;
+            if !completed {
+                break;
+            }
+            self.stale_sweep_cursor = (self.stale_sweep_cursor + 1) % voters_count;
+        }
+
+        let bounty = std::cmp::min(
+            self.stale_sweep_bounty * removed as u128,
+            self.stale_sweep_pool,
+        );
+        if bounty > 0 {
+            self.stale_sweep_pool -= bounty;
+            Promise::new(env::predecessor_account_id()).transfer(bounty);
+        }
+        log!("Removed {} stale votes, bounty {}", removed, bounty);
+        removed
+    }
+
+    /// yoctoNEAR paid per removed stale vote, 0 disables the bounty
+    #[payable]
+    pub fn set_stale_sweep_bounty(&mut self, bounty_per_vote: U128String) {
+        assert_one_yocto();
+        self.assert_only_owner();
+        emit_config_change("stale_sweep_bounty", bounty_per_vote.0);
+        self.stale_sweep_bounty = bounty_per_vote.0;
+    }
+
+    /// adds the attached NEAR to the bounties pool
+    #[payable]
+    pub fn fund_stale_sweep_pool(&mut self) {
+        self.stale_sweep_pool += env::attached_deposit();
+    }
+
+    pub fn get_stale_sweep_info(&self) -> StaleSweepInfo {
+        StaleSweepInfo {
+            cursor: self.stale_sweep_cursor,
+            voters_count: self.voters.len(),
+            bounty_per_vote: self.stale_sweep_bounty.into(),
+            pool: self.stale_sweep_pool.into(),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use near_sdk::{env, CryptoHash};
 /// 7 days in milliseconds
 pub(crate) const SEVEN_DAYS_MS: u64 = 7 * 24 * 60 * 60 * 1000;
 /// 60 days in milliseconds
-const SIXTY_DAYS_MS: u64 = 60 * 24 * 60 * 60 * 1000;
+pub(crate) const SIXTY_DAYS_MS: u64 = 60 * 24 * 60 * 60 * 1000;
 /// timestamp implementation date Aug 1st, 2025
 const DEFAULT_TIMESTAMP: u64 = 1754006400000;
 
@@ -41,6 +41,18 @@ impl crate::MetaVoteContract {
         self.timestamp_storage.insert(&hash_key, &timestamp);
     }
 
+    /// Store a given timestamp, e.g. to keep the original timestamp of a moved vote
+    pub(crate) fn restore_vote_timestamp(
+        &mut self,
+        voter_id: &String,
+        contract_address: &ContractAddress,
+        votable_object_id: &VotableObjId,
+        timestamp: u64,
+    ) {
+        let hash_key = Self::compose_key(voter_id, contract_address, votable_object_id);
+        self.timestamp_storage.insert(&hash_key, &timestamp);
+    }
+
     /// Get the timestamp for a specific vote
     pub(crate) fn get_vote_timestamp(
         &self,
@@ -65,15 +77,19 @@ impl crate::MetaVoteContract {
         self.timestamp_storage.remove(&hash_key);
     }
 
-    /// Returns true if the vote is stale (older than threshold), or if there's no timestamp.
+    /// Returns true if the vote is stale (older than the app threshold), or if there's no timestamp.
+    /// Votes of apps without a stale policy never get stale.
     pub(crate) fn verify_vote_is_stale(
         &self,
         voter_id: &String,
         contract_address: &ContractAddress,
         votable_object_id: &VotableObjId,
     ) -> bool {
+        let policy = match self.internal_get_stale_policy(contract_address) {
+            Some(policy) => policy,
+            None => return false,
+        };
         let vote_timestamp = self.get_vote_timestamp(voter_id, contract_address, votable_object_id);
-        // Vote is stale if it's at least 60 days old
-        env::block_timestamp_ms() >= vote_timestamp + SIXTY_DAYS_MS
+        env::block_timestamp_ms() >= vote_timestamp + policy.stale_after_ms
     }
 }
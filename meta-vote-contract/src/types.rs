@@ -14,6 +14,7 @@ pub type EvmSignature = String;
 
 pub type EpochMillis = u64;
 pub type PositionIndex = u64;
+pub type PositionId = u64;
 
 construct_uint! {
     /// 256-bit unsigned integer.
@@ -24,6 +25,7 @@ construct_uint! {
 #[serde(crate = "near_sdk::serde")]
 pub struct LockingPositionJSON {
     pub index: Option<PositionIndex>,
+    pub id: PositionId,
     pub amount: U128,
     pub locking_period: Days, // unbond_period, kept as locking_period for backwards compat
     pub voting_power: U128,
@@ -33,6 +35,22 @@ pub struct LockingPositionJSON {
     pub is_locked: bool,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ObjectVoterJSON {
+    pub voter_id: VoterId,
+    pub voting_power: U128,
+    pub vote_timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegatorJSON {
+    pub delegator_id: VoterId,
+    pub voting_power: U128,
+    pub delegation_timestamp: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct VotableObjectJSON {
@@ -28,6 +28,21 @@ pub fn apply_bp(amount: u128, bp: u16) -> u128 {
     proportional(amount, bp.into(), 10_000)
 }
 
+/// the attached deposit must pay the storage used since `initial_storage_usage`,
+/// the rest is returned to the caller
+pub fn charge_storage_increase(initial_storage_usage: u64) {
+    let storage_cost = env::storage_usage().saturating_sub(initial_storage_usage) as u128
+        * env::storage_byte_cost();
+    let attached = env::attached_deposit();
+    require!(
+        attached >= storage_cost,
+        format!("Attach {} yoctoNEAR to pay the storage.", storage_cost)
+    );
+    if attached > storage_cost {
+        Promise::new(env::predecessor_account_id()).transfer(attached - storage_cost);
+    }
+}
+
 pub fn generate_hash_id(id: &String) -> CryptoHash {
     env::keccak256_array(id.as_bytes())
 }
@@ -36,6 +51,52 @@ pub fn pseudo_near_address(external_address: &String) -> String {
     format!("{}.evmp.near", external_address)
 }
 
+/// the form evm addresses are stored: lowercase hex without 0x prefix
+pub fn normalize_evm_address(evm_address: &str) -> EvmAddress {
+    evm_address
+        .strip_prefix("0x")
+        .unwrap_or(evm_address)
+        .to_lowercase()
+}
+
+/// decodes a hex string, with or without 0x prefix
+pub fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => u8::from_str_radix(std::str::from_utf8(&[*high, *low]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Recovers the EVM address (lowercase hex, no 0x prefix) that signed `message`
+/// with an EIP-191 `personal_sign` signature (65 bytes r,s,v as hex)
+pub fn recover_evm_address(message: &str, signature: &str) -> Option<EvmAddress> {
+    let signature = hex_decode(signature)?;
+    if signature.len() != 65 {
+        return None;
+    }
+    // v is 27/28 for personal_sign, some wallets use 0/1
+    let v = match signature[64] {
+        27 | 28 => signature[64] - 27,
+        0 | 1 => signature[64],
+        _ => return None,
+    };
+    let prefixed_message = format!("\x19Ethereum Signed Message:\n{}{}", message.len(), message);
+    let hash = env::keccak256_array(prefixed_message.as_bytes());
+    let public_key = env::ecrecover(&hash, &signature[..64], v, true)?;
+    // address is the last 20 bytes of keccak256(public_key)
+    let address_hash = env::keccak256_array(&public_key);
+    Some(
+        address_hash[12..]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
 pub fn assert_at_least_1_mpdao(mpdao_amount: MpDAOAmount) {
     assert!(
         mpdao_amount >= ONE_MPDAO,
@@ -47,6 +108,7 @@ pub fn assert_at_least_1_mpdao(mpdao_amount: MpDAOAmount) {
 /// i.e: 30->0.5x, 60(default)->1, 120->2, 180->3, 240->4, 300->5x –Step: 30days
 /// formula for multiplier is: unbond_days/60
 /// formula for voting power is: govTokenLocked * unbond_days / 60
+/// Default curve, see `vp_curve::calculate_voting_power_with_curve`
 pub fn calculate_voting_power(mpdao_amount: MpDAOAmount, unbond_days: Days) -> u128 {
     // voting power is u128 with 24 decimals (NEAR standard) and mpdao_amount has 6 decimals
     let base_vp = mpdao_amount.checked_mul(E18).expect("vp overflow"); // convert to 24 decimals voting power
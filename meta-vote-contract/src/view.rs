@@ -1,3 +1,4 @@
+use crate::internal::DELEGATED_CONTRACT_CODE;
 use crate::types::*;
 use crate::{voter::VoterJSON, MetaVoteContract, MetaVoteContractExt, StorageKey};
 use near_sdk::{
@@ -188,15 +189,10 @@ impl MetaVoteContract {
     /// This includes the voter's self voting power, plus any delegated voting power received from others,
     /// minus any voting power they have delegated away to others.
     /// Note: delegates cannot delegate away their VP, so either internal_get_delegated_vp or delegated_away_vp will be zero.
+    /// For snapshot voting use get_mpip_voting_power_at.
     pub fn get_mpip_voting_power(&self, voter_id: VoterId) -> U128String {
         let voter = self.internal_get_voter(&voter_id);
-        let self_vp = voter.sum_locked_vp();
-        let delegated_vp = self.internal_get_delegated_vp(&voter_id);
-        let delegated_away_vp = voter.sum_delegated_away_vp();
-        self_vp
-            .saturating_add(delegated_vp)
-            .saturating_sub(delegated_away_vp)
-            .into()
+        self.internal_get_mpip_vp(&voter_id, &voter).into()
     }
 
     /// self-voting power, does not include delegated voting power
@@ -212,10 +208,37 @@ impl MetaVoteContract {
     }
 
     /// delegate: get delegated voting power (voting power received from others)
+    /// Expired delegations count until they are returned.
     pub fn get_delegated_voting_power(&self, voter_id: &VoterId) -> U128String {
         self.internal_get_delegated_vp(voter_id).into()
     }
 
+    /// delegate: who delegated to them, with the voting power and last delegation timestamp
+    pub fn get_delegators(
+        &self,
+        delegate_id: VoterId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<DelegatorJSON> {
+        self.get_voters_for_object(
+            DELEGATED_CONTRACT_CODE.into(),
+            delegate_id,
+            from_index,
+            limit,
+        )
+        .into_iter()
+        .map(|voter| DelegatorJSON {
+            delegator_id: voter.voter_id,
+            voting_power: voter.voting_power,
+            delegation_timestamp: voter.vote_timestamp,
+        })
+        .collect()
+    }
+
+    pub fn get_delegator_count(&self, delegate_id: VoterId) -> u64 {
+        self.get_voters_for_object_count(DELEGATED_CONTRACT_CODE.into(), delegate_id)
+    }
+
     /// voter: includes self and delegated voting power
     pub fn voter_total_voting_power(&self, voter_id: VoterId) -> U128String {
         let voter = self.internal_get_voter(&voter_id);
@@ -257,12 +280,12 @@ impl MetaVoteContract {
 
     pub fn get_locking_position(
         &self,
-        index: PositionIndex,
+        position_id: PositionId,
         voter_id: VoterId,
     ) -> Option<LockingPositionJSON> {
         let voter = self.internal_get_voter(&voter_id);
-        match voter.locking_positions.get(index) {
-            Some(locking_position) => Some(locking_position.to_json(Some(index))),
+        match voter.find_position_index_by_id(position_id) {
+            Some(index) => Some(voter.get_position(index).to_json(Some(index))),
             None => None,
         }
     }
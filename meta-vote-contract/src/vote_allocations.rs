@@ -0,0 +1,216 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// A vote stored as basis points of the voter full voting power (locked + delegated)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VoteAllocationBp {
+    pub contract_address: ContractAddress,
+    pub votable_object_id: VotableObjId,
+    pub bp: u16,
+}
+
+impl MetaVoteContract {
+    /// call this after the voting power of a voter changed.
+    /// Follows the voter percentage delegations & allocations and records the vp checkpoints.
+    pub(crate) fn internal_after_vp_change(&mut self, voter_id: &String, voter: &mut Voter) {
+        self.internal_follow_vp_shares(voter_id, voter);
+        self.write_vp_checkpoints(voter_id, voter);
+    }
+
+    /// returns the expired delegations, re-materializes the percentage delegations,
+    /// then the percentage allocations
+    pub(crate) fn internal_follow_vp_shares(&mut self, voter_id: &String, voter: &mut Voter) {
+        self.internal_expire_delegations_of(voter_id, voter);
+        self.internal_follow_delegation(voter_id, voter);
+        self.internal_follow_vote_allocations(voter_id, voter);
+    }
+
+    /// same as internal_after_vp_change, for a delegate whose Voter is not loaded
+    pub(crate) fn internal_after_delegate_vp_change(&mut self, delegate_id: &String) {
+        if let Some(mut delegate) = self.voters.get(delegate_id) {
+            self.internal_after_vp_change(delegate_id, &mut delegate);
+            self.voters.insert(delegate_id, &delegate);
+        }
+    }
+
+    /// re-materializes the absolute votes of a voter with percentage allocations.
+    /// Decreases first, increases are capped to the available voting power and the app rules.
+    /// Locked votes (lock_votes_in_*) are not decreased.
+    fn internal_follow_vote_allocations(&mut self, voter_id: &String, voter: &mut Voter) {
+        let allocations = match self.vote_allocations_bp.get(voter_id) {
+            Some(allocations) => allocations.clone(),
+            None => return,
+        };
+        self.update_vp_available(voter_id, voter);
+        let full_voting_power = voter.sum_locked_vp() + self.internal_get_delegated_vp(voter_id);
+        let targets: Vec<(&VoteAllocationBp, u128, u128)> = allocations
+            .iter()
+            .map(|allocation| {
+                let votes = voter
+                    .vote_positions
+                    .get(&allocation.contract_address)
+                    .and_then(|votes_for_address| {
+                        votes_for_address.get(&allocation.votable_object_id)
+                    })
+                    .unwrap_or(0);
+                (
+                    allocation,
+                    votes,
+                    apply_bp(full_voting_power, allocation.bp),
+                )
+            })
+            .collect();
+
+        for (allocation, votes, target) in targets.iter() {
+            if target >= votes
+                || self
+                    .are_votes_locked(&allocation.contract_address, &allocation.votable_object_id)
+            {
+                continue;
+            }
+            if *target == 0 {
+                self.internal_remove_voting_position(
+                    voter_id,
+                    voter,
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+            } else {
+                self.internal_rebalance_voting_position(
+                    voter_id,
+                    voter,
+                    *target,
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+            }
+        }
+        for (allocation, votes, target) in targets.iter() {
+            let room = std::cmp::min(
+                self.internal_vote_pool_room(voter_id, voter, &allocation.contract_address),
+                self.internal_vote_room(
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                ),
+            );
+            if target <= votes || room == 0 {
+                continue;
+            }
+            let target = votes + std::cmp::min(target - votes, room);
+            if *votes == 0 {
+                self.internal_create_voting_position(
+                    voter_id,
+                    voter,
+                    target,
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+                MetaVoteEvent::Vote(VoteData {
+                    account_id: voter_id,
+                    contract_address: &allocation.contract_address,
+                    votable_object_id: &allocation.votable_object_id,
+                    voting_power: target.into(),
+                })
+                .emit();
+            } else {
+                self.internal_rebalance_voting_position(
+                    voter_id,
+                    voter,
+                    target,
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// Opt-in: store the caller votes as basis points of their voting power.
+    /// The absolute votes follow the voting power when it changes (lock, unlock, extend, etc.).
+    /// Votes for objects removed from the allocations are unvoted.
+    /// An empty list opts out, the current votes are kept as absolute votes.
+    pub fn set_vote_allocations_bp(&mut self, allocations: Vec<VoteAllocationBp>) {
+        let voter_id = env::predecessor_account_id().as_str().to_string();
+        self.internal_touch_delegations(&voter_id);
+        let mut voter = self.internal_get_voter_or_panic(&voter_id);
+        require!(
+            allocations.len() <= MAX_VOTE_ALLOCATIONS,
+            format!("Cannot exceed {} vote allocations.", MAX_VOTE_ALLOCATIONS)
+        );
+        let mut total_bp = 0_u32;
+        for (index, allocation) in allocations.iter().enumerate() {
+            require!(
+                allocation.contract_address != DELEGATED_CONTRACT_CODE,
+                "Delegations cannot be percentage allocations."
+            );
+            require!(
+                !allocations[..index].iter().any(|other| {
+                    other.contract_address == allocation.contract_address
+                        && other.votable_object_id == allocation.votable_object_id
+                }),
+                "Duplicated vote allocation."
+            );
+            total_bp += allocation.bp as u32;
+        }
+        require!(
+            total_bp <= 10000,
+            "Total allocations cannot exceed 10000 bp."
+        );
+
+        // unvote the objects no longer allocated, opting out keeps them
+        let previous = if allocations.is_empty() {
+            Vec::new()
+        } else {
+            self.vote_allocations_bp
+                .get(&voter_id)
+                .cloned()
+                .unwrap_or_default()
+        };
+        for allocation in previous.iter() {
+            let still_allocated = allocations.iter().any(|other| {
+                other.contract_address == allocation.contract_address
+                    && other.votable_object_id == allocation.votable_object_id
+            });
+            let has_votes = voter
+                .vote_positions
+                .get(&allocation.contract_address)
+                .map(|votes_for_address| {
+                    votes_for_address
+                        .get(&allocation.votable_object_id)
+                        .is_some()
+                })
+                .unwrap_or(false);
+            if !still_allocated && has_votes {
+                self.assert_votes_not_locked(
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+                self.internal_remove_voting_position(
+                    &voter_id,
+                    &mut voter,
+                    &allocation.contract_address,
+                    &allocation.votable_object_id,
+                );
+            }
+        }
+
+        if allocations.is_empty() {
+            self.vote_allocations_bp.remove(&voter_id);
+        } else {
+            self.vote_allocations_bp
+                .insert(voter_id.clone(), allocations);
+            self.internal_follow_vote_allocations(&voter_id, &mut voter);
+        }
+        self.voters.insert(&voter_id, &voter);
+    }
+
+    pub fn get_vote_allocations_bp(&self, voter_id: VoterId) -> Vec<VoteAllocationBp> {
+        self.vote_allocations_bp
+            .get(&voter_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
@@ -13,7 +13,7 @@ pub struct VoterJSON {
     pub delegated_vp: U128String,                    // how much of the available vp is delegated
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshSerialize)]
 pub struct Voter {
     pub balance: MpDAOAmount,
     pub locking_positions: Vector<LockingPosition>,
@@ -21,6 +21,25 @@ pub struct Voter {
     /// can be recomputed from scratch by operator_recompute_available_vp()
     pub available_voting_power: u128,
     pub vote_positions: UnorderedMap<ContractAddress, UnorderedMap<VotableObjId, u128>>,
+    /// received delegated voting power restricted to a scope (not DelegationScope::All)
+    pub scoped_delegated_vp: Vec<(DelegationScope, u128)>,
+}
+
+/// voters stored before scoped delegations have no scoped_delegated_vp bytes
+impl BorshDeserialize for Voter {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            balance: BorshDeserialize::deserialize(buf)?,
+            locking_positions: BorshDeserialize::deserialize(buf)?,
+            available_voting_power: BorshDeserialize::deserialize(buf)?,
+            vote_positions: BorshDeserialize::deserialize(buf)?,
+            scoped_delegated_vp: if buf.is_empty() {
+                Vec::new()
+            } else {
+                BorshDeserialize::deserialize(buf)?
+            },
+        })
+    }
 }
 
 impl Voter {
@@ -34,6 +53,7 @@ impl Voter {
             vote_positions: UnorderedMap::new(StorageKey::VotePosition {
                 hash_id: generate_hash_id(id),
             }),
+            scoped_delegated_vp: Vec::new(),
         }
     }
 
@@ -95,15 +115,59 @@ impl Voter {
             .unwrap_or(0)
     }
 
-    pub(crate) fn find_locked_position(&self, unbond_days: Days) -> Option<u64> {
-        let mut index = 0_u64;
-        for locking_position in self.locking_positions.iter() {
-            if locking_position.locking_period == unbond_days && locking_position.is_locked() {
-                return Some(index);
-            }
-            index += 1;
+    /// received delegated voting power restricted to the scope
+    pub(crate) fn get_scoped_delegated_vp(&self, scope: &DelegationScope) -> u128 {
+        self.scoped_delegated_vp
+            .iter()
+            .find(|(other, _)| other == scope)
+            .map(|(_, voting_power)| *voting_power)
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn add_scoped_delegated_vp(&mut self, scope: &DelegationScope, voting_power: u128) {
+        if *scope == DelegationScope::All {
+            return;
+        }
+        match self
+            .scoped_delegated_vp
+            .iter_mut()
+            .find(|(other, _)| other == scope)
+        {
+            Some((_, scoped)) => *scoped += voting_power,
+            None => self.scoped_delegated_vp.push((scope.clone(), voting_power)),
+        }
+    }
+
+    pub(crate) fn remove_scoped_delegated_vp(
+        &mut self,
+        scope: &DelegationScope,
+        voting_power: u128,
+    ) {
+        if let Some((_, scoped)) = self
+            .scoped_delegated_vp
+            .iter_mut()
+            .find(|(other, _)| other == scope)
+        {
+            *scoped = scoped.saturating_sub(voting_power);
         }
-        None
+        self.scoped_delegated_vp.retain(|(_, scoped)| *scoped > 0);
+    }
+
+    /// received delegated voting power restricted to a scope
+    pub(crate) fn sum_scoped_delegated_vp(&self) -> u128 {
+        self.scoped_delegated_vp
+            .iter()
+            .map(|(_, voting_power)| voting_power)
+            .sum()
+    }
+
+    /// received delegated voting power that only counts for votes in apps, not for MPIPs
+    pub(crate) fn sum_app_scoped_delegated_vp(&self) -> u128 {
+        self.scoped_delegated_vp
+            .iter()
+            .filter(|(scope, _)| matches!(scope, DelegationScope::App { .. }))
+            .map(|(_, voting_power)| voting_power)
+            .sum()
     }
 
     pub(crate) fn get_position(&self, index: PositionIndex) -> LockingPosition {
@@ -112,6 +176,26 @@ impl Voter {
             .expect("Index out of range!")
     }
 
+    pub(crate) fn get_position_index(&self, id: PositionId) -> PositionIndex {
+        self.find_position_index_by_id(id)
+            .expect("Locking position not found!")
+    }
+
+    /// converts position ids into the current Vector indexes
+    pub(crate) fn get_position_indexes(&self, ids: &[PositionId]) -> Vec<PositionIndex> {
+        ids.iter().map(|id| self.get_position_index(*id)).collect()
+    }
+
+    pub(crate) fn find_position_index_by_id(&self, id: PositionId) -> Option<PositionIndex> {
+        if id == 0 {
+            return None;
+        }
+        self.locking_positions
+            .iter()
+            .position(|locking_position| locking_position.id == id)
+            .map(|index| index as PositionIndex)
+    }
+
     pub(crate) fn remove_position(&mut self, index: PositionIndex) {
         self.locking_positions.swap_remove(index);
     }
@@ -129,15 +213,11 @@ impl Voter {
             }))
     }
 
-    pub(crate) fn get_unlocked_position_indexes(&self) -> Vec<PositionIndex> {
+    pub(crate) fn get_unlocked_position_ids(&self) -> Vec<PositionId> {
         let mut result = Vec::new();
-        for index in 0..self.locking_positions.len() {
-            let locking_position = self
-                .locking_positions
-                .get(index)
-                .expect("Locking position not found!");
+        for locking_position in self.locking_positions.iter() {
             if locking_position.is_unlocked() {
-                result.push(index);
+                result.push(locking_position.id);
             }
         }
         result
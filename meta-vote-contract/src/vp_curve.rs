@@ -0,0 +1,147 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// A point of the voting power curve: locking `days` give `multiplier_bp` (10000 = 1x)
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VpCurvePoint {
+    pub days: Days,
+    pub multiplier_bp: u32,
+}
+
+/// multiplier for unbond_days, linear interpolation between the curve points.
+/// Below the first point and above the last point the multiplier is flat.
+fn curve_multiplier_bp(curve: &[VpCurvePoint], unbond_days: Days) -> u32 {
+    let first = &curve[0];
+    if unbond_days <= first.days {
+        return first.multiplier_bp;
+    }
+    for segment in curve.windows(2) {
+        let (from, to) = (&segment[0], &segment[1]);
+        if unbond_days <= to.days {
+            let delta_bp = (to.multiplier_bp - from.multiplier_bp) as u64;
+            let delta_days = (unbond_days - from.days) as u64;
+            let segment_days = (to.days - from.days) as u64;
+            return from.multiplier_bp + (delta_bp * delta_days / segment_days) as u32;
+        }
+    }
+    curve[curve.len() - 1].multiplier_bp
+}
+
+/// Voting power using a curve, an empty curve is the linear unbond_days/60 multiplier
+pub fn calculate_voting_power_with_curve(
+    mpdao_amount: MpDAOAmount,
+    unbond_days: Days,
+    curve: &[VpCurvePoint],
+) -> u128 {
+    if curve.is_empty() {
+        return calculate_voting_power(mpdao_amount, unbond_days);
+    }
+    // voting power is u128 with 24 decimals (NEAR standard) and mpdao_amount has 6 decimals
+    let base_vp = mpdao_amount.checked_mul(E18).expect("vp overflow");
+    proportional(
+        base_vp,
+        curve_multiplier_bp(curve, unbond_days).into(),
+        10_000,
+    )
+}
+
+impl MetaVoteContract {
+    pub(crate) fn internal_calculate_voting_power(
+        &self,
+        mpdao_amount: MpDAOAmount,
+        unbond_days: Days,
+    ) -> u128 {
+        calculate_voting_power_with_curve(mpdao_amount, unbond_days, &self.vp_curve)
+    }
+
+    fn assert_valid_vp_curve(curve: &[VpCurvePoint]) {
+        require!(
+            curve.len() <= MAX_VP_CURVE_POINTS,
+            format!(
+                "The curve cannot have more than {} points",
+                MAX_VP_CURVE_POINTS
+            )
+        );
+        for segment in curve.windows(2) {
+            require!(
+                segment[0].days < segment[1].days,
+                "Curve days must be strictly increasing"
+            );
+            require!(
+                segment[0].multiplier_bp <= segment[1].multiplier_bp,
+                "Curve multipliers cannot decrease"
+            );
+        }
+    }
+
+    /// recomputes the vp of all the voter positions with the current curve.
+    /// Returns true if the voter vp changed.
+    fn internal_recompute_voter_vp(&mut self, voter: &mut Voter) -> bool {
+        let mut changed = false;
+        for index in 0..voter.locking_positions.len() {
+            let mut locking_position = voter.get_position(index);
+            let voting_power = self.internal_calculate_voting_power(
+                locking_position.amount,
+                locking_position.locking_period,
+            );
+            if voting_power == locking_position.voting_power {
+                continue;
+            }
+            // unlocking positions vp is not part of total_voting_power
+            if locking_position.is_locked() {
+                self.total_voting_power =
+                    self.total_voting_power + voting_power - locking_position.voting_power;
+                changed = true;
+            }
+            locking_position.voting_power = voting_power;
+            voter.locking_positions.replace(index, &locking_position);
+        }
+        changed
+    }
+}
+
+#[near_bindgen]
+impl MetaVoteContract {
+    /// Sets the voting power curve for new and updated locking positions.
+    /// Existing positions are recomputed with `operator_recompute_voting_power`.
+    /// Can be called by the owner or by the MPIP contract executing a proposal action.
+    #[payable]
+    pub fn set_vp_curve(&mut self, curve: Vec<VpCurvePoint>) {
+        assert_one_yocto();
+        self.assert_owner_or_mpip();
+        Self::assert_valid_vp_curve(&curve);
+        emit_config_change(
+            "vp_curve",
+            near_sdk::serde_json::to_string(&curve).expect("curve serialization"),
+        );
+        self.vp_curve = curve;
+    }
+
+    /// empty means the linear multiplier (unbond_days / 60)
+    pub fn get_vp_curve(&self) -> Vec<VpCurvePoint> {
+        self.vp_curve.clone()
+    }
+
+    /// after a curve change: recomputes the voting power of the voters positions,
+    /// fixing total_voting_power. Votes exceeding the new voting power are removed.
+    pub fn operator_recompute_voting_power(&mut self, from_index: u32, limit: u32) -> u32 {
+        self.assert_operator();
+        let keys = self.voters.keys_as_vector();
+        let start = from_index as u64;
+        let end = std::cmp::min(start + limit as u64, keys.len());
+        let voter_ids: Vec<String> = (start..end).map(|index| keys.get(index).unwrap()).collect();
+        let mut count = 0;
+        for voter_id in voter_ids {
+            self.internal_seed_vp_checkpoints(&voter_id);
+            let mut voter = self.voters.get(&voter_id).unwrap();
+            if self.internal_recompute_voter_vp(&mut voter) {
+                self.adjust_voter_voting_power(&voter_id, &mut voter);
+                self.internal_after_vp_change(&voter_id, &mut voter);
+                self.voters.insert(&voter_id, &voter);
+                count += 1;
+            }
+        }
+        count
+    }
+}
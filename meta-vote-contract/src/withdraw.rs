@@ -11,11 +11,13 @@ impl MetaVoteContract {
     fn internal_withdraw(
         &mut self,
         voter_id: AccountId,
-        position_index_list: Vec<PositionIndex>,
+        position_id_list: Vec<PositionId>,
         optional_amount_to_withdraw: Option<u128>,
     ) {
         assert_one_yocto();
         let mut voter = self.internal_get_voter_or_panic(&voter_id.to_string());
+        self.internal_assign_position_ids(&mut voter);
+        let position_index_list = voter.get_position_indexes(&position_id_list);
         // Clear locking positions, and increase the voter balance.
         if position_index_list.len() > 0 {
             voter.clear_fully_unlocked_positions(position_index_list);
@@ -30,9 +32,16 @@ impl MetaVoteContract {
         );
 
         voter.balance -= total_to_withdraw;
+        MetaVoteEvent::Withdraw(AmountData {
+            account_id: voter_id.as_str(),
+            amount: total_to_withdraw.into(),
+        })
+        .emit();
 
         if voter.is_empty() {
             self.voters.remove(&voter_id.to_string());
+            self.vote_allocations_bp.remove(&voter_id.to_string());
+            self.delegations_bp.remove(&voter_id.to_string());
             log!("GODSPEED: {} is no longer part of Meta Vote!", &voter_id);
         } else {
             self.voters.insert(&voter_id.to_string(), &voter);
@@ -41,15 +50,11 @@ impl MetaVoteContract {
     }
 
     #[payable]
-    pub fn withdraw(
-        &mut self,
-        position_index_list: Vec<PositionIndex>,
-        amount_from_balance: U128String,
-    ) {
+    pub fn withdraw(&mut self, position_id_list: Vec<PositionId>, amount_from_balance: U128String) {
         let amount_to_withdraw = amount_from_balance.0;
         self.internal_withdraw(
             env::predecessor_account_id(),
-            position_index_list,
+            position_id_list,
             if amount_to_withdraw == 0 {
                 None
             } else {
@@ -60,9 +65,11 @@ impl MetaVoteContract {
 
     #[payable]
     pub fn withdraw_all(&mut self) {
-        let voter = self.internal_get_voter_or_panic(&env::predecessor_account_id().to_string());
-        let position_index_list = voter.get_unlocked_position_indexes();
-        self.internal_withdraw(env::predecessor_account_id(), position_index_list, None);
+        let mut voter =
+            self.internal_get_voter_or_panic(&env::predecessor_account_id().to_string());
+        self.internal_assign_position_ids(&mut voter);
+        let position_id_list = voter.get_unlocked_position_ids();
+        self.internal_withdraw(env::predecessor_account_id(), position_id_list, None);
     }
 
     // *************************
@@ -86,19 +93,13 @@ impl MetaVoteContract {
         let amount = amount.0;
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Successful(_) => {
-                log!(
-                    "WITHDRAW: {} mpDAO transfer to {}",
-                    amount,
-                    voter_id.to_string()
-                );
-            }
+            PromiseResult::Successful(_) => {}
             PromiseResult::Failed => {
-                log!(
-                    "FAILED: {} mpDAO not transferred. Recovering {} state.",
-                    amount,
-                    &voter_id.to_string()
-                );
+                MetaVoteEvent::WithdrawFailed(AmountData {
+                    account_id: voter_id.as_str(),
+                    amount: amount.into(),
+                })
+                .emit();
                 self.restore_transfer_to_mpdao(amount, voter_id);
             }
         };
@@ -138,20 +139,15 @@ impl MetaVoteContract {
         let amount = amount.0;
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Successful(_) => {
-                log!(
-                    "{} WITHDRAWN {} stNEAR to {}",
-                    source_voter,
-                    amount,
-                    receiver
-                );
-            }
+            PromiseResult::Successful(_) => {}
             PromiseResult::Failed => {
-                log!(
-                    "FAILED: {} stNEAR not transferred. Recovering {} state.",
-                    amount,
-                    source_voter
-                );
+                MetaVoteEvent::TransferFailed(ClaimData {
+                    account_id: source_voter,
+                    receiver_id: receiver.as_str(),
+                    token_contract: self.stnear_token_contract_address.as_str(),
+                    amount: amount.into(),
+                })
+                .emit();
                 self.add_claimable_stnear(source_voter, amount);
             }
         };
@@ -189,20 +185,15 @@ impl MetaVoteContract {
         let amount = amount.0;
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Successful(_) => {
-                log!(
-                    "{} WITHDRAWN {} unlocked mpDAO to {}",
-                    source_voter,
-                    amount,
-                    receiver
-                );
-            }
+            PromiseResult::Successful(_) => {}
             PromiseResult::Failed => {
-                log!(
-                    "FAILED: {} unlocked mpDAO not transferred. Recovering {} state.",
-                    amount,
-                    source_voter
-                );
+                MetaVoteEvent::TransferFailed(ClaimData {
+                    account_id: source_voter,
+                    receiver_id: receiver.as_str(),
+                    token_contract: self.mpdao_token_contract_address.as_str(),
+                    amount: amount.into(),
+                })
+                .emit();
                 self.add_claimable_unlocked_mpdao(source_voter, amount);
             }
         };
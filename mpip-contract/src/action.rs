@@ -0,0 +1,202 @@
+use crate::*;
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{Gas, PromiseResult};
+
+/// On-chain action performed by the governance contract when a proposal is accepted.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MpipAction {
+    /// call `method_name` on `receiver_id`, attaching `deposit` yoctoNEAR and `gas`
+    FunctionCall {
+        receiver_id: AccountId,
+        method_name: String,
+        args: Base64VecU8,
+        deposit: U128,
+        gas: U64,
+    },
+    /// transfer NEAR from the governance contract balance
+    Transfer {
+        receiver_id: AccountId,
+        amount: U128,
+    },
+    /// NEP-141 transfer of tokens held by the governance contract
+    FtTransfer {
+        token_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MpipActionStatus {
+    Pending,   // dispatched, waiting for the callback
+    Succeeded, // the receipt finished successfully
+    Failed,    // the receipt failed, the proposal is still marked as executed
+}
+
+impl MpipAction {
+    pub(crate) fn assert_valid(&self) {
+        match self {
+            MpipAction::FunctionCall {
+                method_name, gas, ..
+            } => {
+                require!(!method_name.is_empty(), "Action method_name is empty.");
+                require!(
+                    gas.0 > 0 && gas.0 <= MAX_GAS_PER_MPIP_ACTION.0,
+                    format!(
+                        "Action gas must be between 1 and {} gas.",
+                        MAX_GAS_PER_MPIP_ACTION.0
+                    )
+                );
+            }
+            MpipAction::Transfer { amount, .. } => {
+                require!(amount.0 > 0, "Transfer amount must be greater than 0.");
+            }
+            MpipAction::FtTransfer { amount, .. } => {
+                require!(amount.0 > 0, "FT transfer amount must be greater than 0.");
+            }
+        }
+    }
+
+    /// gas the action takes from the execute_proposal transaction, including its callback
+    fn required_gas(&self) -> u64 {
+        let attached_gas = match self {
+            MpipAction::FunctionCall { gas, .. } => gas.0,
+            MpipAction::Transfer { .. } => 0,
+            MpipAction::FtTransfer { .. } => GAS_FOR_FT_TRANSFER.0,
+        };
+        attached_gas + GAS_FOR_RESOLVE_ACTION.0 + GAS_FOR_ACTION_DISPATCH.0
+    }
+
+    fn into_promise(self) -> Promise {
+        match self {
+            MpipAction::FunctionCall {
+                receiver_id,
+                method_name,
+                args,
+                deposit,
+                gas,
+            } => {
+                Promise::new(receiver_id).function_call(method_name, args.0, deposit.0, Gas(gas.0))
+            }
+            MpipAction::Transfer {
+                receiver_id,
+                amount,
+            } => Promise::new(receiver_id).transfer(amount.0),
+            MpipAction::FtTransfer {
+                token_id,
+                receiver_id,
+                amount,
+                memo,
+            } => ext_ft_core::ext(token_id)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .with_attached_deposit(1)
+                .ft_transfer(receiver_id, amount, memo),
+        }
+    }
+}
+
+impl MpipContract {
+    pub(crate) fn assert_valid_actions(&self, actions: &Vec<MpipAction>) {
+        require!(
+            actions.len() <= MAX_ACTIONS_PER_MPIP,
+            format!(
+                "A MPIP cannot have more than {} actions.",
+                MAX_ACTIONS_PER_MPIP
+            )
+        );
+        for action in actions {
+            action.assert_valid();
+        }
+        let total_gas: u64 = GAS_FOR_EXECUTE_PROPOSAL.0
+            + actions
+                .iter()
+                .map(|action| action.required_gas())
+                .sum::<u64>();
+        require!(
+            total_gas <= MAX_PREPAID_GAS.0,
+            format!(
+                "The actions need {} gas to execute, more than the {} gas of a transaction.",
+                total_gas, MAX_PREPAID_GAS.0
+            )
+        );
+    }
+
+    pub(crate) fn internal_set_proposal_actions(
+        &mut self,
+        mpip_id: MpipId,
+        actions: Vec<MpipAction>,
+    ) {
+        self.assert_valid_actions(&actions);
+        if actions.is_empty() {
+            self.proposal_actions.remove(&mpip_id);
+        } else {
+            self.proposal_actions.insert(&mpip_id, &actions);
+        }
+    }
+
+    /// Marks the proposal as executed and dispatches every action as an independent promise.
+    /// Each promise has a callback that records the action result in `proposal_actions_status`.
+    pub(crate) fn internal_execute_proposal(&mut self, mpip_id: MpipId) {
+        let mut proposal = self.internal_get_proposal(&mpip_id);
+        proposal.executed = true;
+        self.proposals.insert(&mpip_id, &proposal);
+
+        let actions = self.proposal_actions.get(&mpip_id).unwrap_or_default();
+        if actions.is_empty() {
+            return;
+        }
+        self.proposal_actions_status
+            .insert(&mpip_id, &vec![MpipActionStatus::Pending; actions.len()]);
+        for (index, action) in actions.into_iter().enumerate() {
+            action.into_promise().then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_ACTION)
+                    .on_mpip_action_executed(mpip_id, index as u32),
+            );
+        }
+        log!("MPIP {} executed, dispatched its actions.", mpip_id);
+    }
+}
+
+#[near_bindgen]
+impl MpipContract {
+    #[private]
+    pub fn on_mpip_action_executed(&mut self, mpip_id: MpipId, action_index: u32) -> bool {
+        let succeeded = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => true,
+            PromiseResult::Failed => false,
+        };
+        let mut status = self
+            .proposal_actions_status
+            .get(&mpip_id)
+            .expect("MPIP has no dispatched actions");
+        status[action_index as usize] = if succeeded {
+            MpipActionStatus::Succeeded
+        } else {
+            MpipActionStatus::Failed
+        };
+        self.proposal_actions_status.insert(&mpip_id, &status);
+        if !succeeded {
+            log!("FAILED: MPIP {} action #{} failed.", mpip_id, action_index);
+        }
+        succeeded
+    }
+
+    pub fn get_proposal_actions(&self, mpip_id: MpipId) -> Vec<MpipAction> {
+        self.proposal_actions.get(&mpip_id).unwrap_or_default()
+    }
+
+    /// Status of each action, empty if the proposal actions were not dispatched yet.
+    pub fn get_proposal_actions_status(&self, mpip_id: MpipId) -> Vec<MpipActionStatus> {
+        self.proposal_actions_status
+            .get(&mpip_id)
+            .unwrap_or_default()
+    }
+}
@@ -8,6 +8,26 @@ pub const ONE_HUNDRED: u16 = 10_000;
 /// Amount of gas for fungible token transfers.
 pub const GAS_FOR_GET_VOTING_POWER: Gas = Gas(10 * TGAS);
 pub const GAS_FOR_RESOLVE_VOTE: Gas = Gas(11 * TGAS);
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas(47 * TGAS);
+pub const GAS_FOR_RESOLVE_ACTION: Gas = Gas(5 * TGAS);
+
+/// Limits for the on-chain actions attached to a MPIP.
+pub const MAX_ACTIONS_PER_MPIP: usize = 8;
+pub const MAX_GAS_PER_MPIP_ACTION: Gas = Gas(150 * TGAS);
+/// All the actions are dispatched in the execute_proposal transaction, they must fit in it.
+pub const MAX_PREPAID_GAS: Gas = Gas(300 * TGAS);
+/// execute_proposal itself, without the actions
+pub const GAS_FOR_EXECUTE_PROPOSAL: Gas = Gas(20 * TGAS);
+/// creating the action and callback receipts
+pub const GAS_FOR_ACTION_DISPATCH: Gas = Gas(5 * TGAS);
+
+pub const DAY_IN_MS: u64 = 24 * 60 * 60 * 1000;
+/// Default timelock: accepted proposals wait 2 days before execution, and expire 14 days after that.
+pub const DEFAULT_EXECUTION_DELAY: u64 = 2 * DAY_IN_MS;
+pub const DEFAULT_GRACE_PERIOD: u64 = 14 * DAY_IN_MS;
+/// The timelock cannot be turned off, and queued proposals have time to be executed.
+pub const MIN_EXECUTION_DELAY: u64 = DAY_IN_MS;
+pub const MIN_GRACE_PERIOD: u64 = DAY_IN_MS;
 
 #[derive(BorshSerialize, BorshDeserialize, BorshStorageKey)]
 pub enum StorageKey {
@@ -17,4 +37,7 @@ pub enum StorageKey {
     Voters,
     Proposers,
     Votes { hash_id: CryptoHash },
+    MpipActions,
+    MpipActionsStatus,
+    QueuedMpips,
 }
\ No newline at end of file
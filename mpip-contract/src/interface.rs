@@ -1,6 +1,6 @@
 use crate::types::{MpipId, VoterId};
 use crate::*;
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{U128, U64};
 use near_sdk::{ext_contract, AccountId};
 
 #[allow(dead_code)]
@@ -11,6 +11,7 @@ pub trait ExtMetaVote {
     //fn get_all_locking_positions(&self, voter_id: VoterId);
     fn get_total_voting_power(&self);
     fn get_mpip_voting_power(&self, voter_id: VoterId);
+    fn get_mpip_voting_power_at(&self, voter_id: VoterId, timestamp: U64);
 }
 
 #[allow(dead_code)]
@@ -31,5 +32,6 @@ pub trait SelfMetaVote {
         body: String,
         data: String,
         extra: String,
+        actions: Vec<MpipAction>,
     );
 }
@@ -1,5 +1,6 @@
 use crate::constants::*;
 use crate::interface::*;
+use action::{MpipAction, MpipActionStatus};
 use mpip::{Mpip, MpipJSON, MpipState};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::unordered_map::UnorderedMap;
@@ -12,9 +13,11 @@ use vote::{Vote, VoteJson, VoteType};
 use vote_counting::{ProposalVote, ProposalVoteJson};
 use voter::{Voter, VoterJson};
 
+mod action;
 mod constants;
 mod interface;
 mod internal;
+mod migrate;
 mod mpip;
 mod types;
 mod utils;
@@ -58,6 +61,16 @@ pub struct MpipContract {
     /// If a quorum is set to 50%, this means that 50% of all circulating $mpDAO need to vote yes for the proposal to pass.
     // Percent is denominated in basis points 100% equals 10_000 basis points.
     pub quorum_floor: BasisPoints,
+
+    /// On-chain actions executed when a proposal is accepted, and their results - added 2026-10
+    pub proposal_actions: UnorderedMap<MpipId, Vec<MpipAction>>,
+    pub proposal_actions_status: UnorderedMap<MpipId, Vec<MpipActionStatus>>,
+
+    /// Timelock: accepted proposals are queued with an eta (execution timestamp) - added 2026-10
+    /// and expire if they are not executed before eta + grace_period, fixed when queued.
+    pub queued_proposals: UnorderedMap<MpipId, (EpochMillis, EpochMillis)>, // (eta, expires_at)
+    pub execution_delay: EpochMillis,
+    pub grace_period: EpochMillis,
 }
 
 #[near_bindgen]
@@ -97,6 +110,11 @@ impl MpipContract {
             votes: UnorderedMap::new(StorageKey::MpipVotes),
             voters: UnorderedMap::new(StorageKey::Voters),
             proposers: UnorderedMap::new(StorageKey::Proposers),
+            proposal_actions: UnorderedMap::new(StorageKey::MpipActions),
+            proposal_actions_status: UnorderedMap::new(StorageKey::MpipActionsStatus),
+            queued_proposals: UnorderedMap::new(StorageKey::QueuedMpips),
+            execution_delay: DEFAULT_EXECUTION_DELAY,
+            grace_period: DEFAULT_GRACE_PERIOD,
         }
     }
 
@@ -122,6 +140,33 @@ impl MpipContract {
         self.admin_id = new_value;
     }
 
+    /// Update the timelock delay in milliseconds between the end of the voting and the execution.
+    pub fn update_execution_delay(&mut self, new_value: U64) {
+        self.assert_only_admin();
+        require!(
+            new_value.0 >= MIN_EXECUTION_DELAY,
+            format!(
+                "The execution delay cannot be less than {} ms.",
+                MIN_EXECUTION_DELAY
+            )
+        );
+        self.execution_delay = new_value.0;
+    }
+
+    /// Update the time in milliseconds a proposal can be executed after its eta.
+    /// Proposals already queued keep their expiration.
+    pub fn update_grace_period(&mut self, new_value: U64) {
+        self.assert_only_admin();
+        require!(
+            new_value.0 >= MIN_GRACE_PERIOD,
+            format!(
+                "The grace period cannot be less than {} ms.",
+                MIN_GRACE_PERIOD
+            )
+        );
+        self.grace_period = new_value.0;
+    }
+
     pub fn pay_to_account(&mut self, amount: U128, to: AccountId) -> Promise {
         self.assert_only_admin();
         Promise::new(to).transfer(amount.0)
@@ -212,9 +257,12 @@ impl MpipContract {
         body: String,
         data: String,
         extra: String,
+        actions: Option<Vec<MpipAction>>,
     ) {
         self.assert_open_for_new_mpips();
         self.assert_proposal_storage_is_covered();
+        let actions = actions.unwrap_or_default();
+        self.assert_valid_actions(&actions);
         ext_metavote::ext(self.meta_vote_contract_address.clone())
             .with_static_gas(GAS_FOR_GET_VOTING_POWER)
             .with_attached_deposit(1)
@@ -222,7 +270,7 @@ impl MpipContract {
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_FOR_RESOLVE_VOTE)
-                    .create_proposal_callback(title, short_description, body, data, extra),
+                    .create_proposal_callback(title, short_description, body, data, extra, actions),
             );
     }
 
@@ -234,11 +282,13 @@ impl MpipContract {
         body: String,
         data: String,
         extra: String,
+        actions: Vec<MpipAction>,
     ) -> MpipId {
         let total_v_power = self.internal_get_user_total_voting_power_from_promise();
         self.assert_proposal_threshold(total_v_power);
         let id = self.proposals.len() as MpipId;
         self.internal_create_proposal(id, title, short_description, body, data, extra);
+        self.internal_set_proposal_actions(id, actions);
         id
     }
 
@@ -258,6 +308,7 @@ impl MpipContract {
         body: String,
         data: String,
         extra: String,
+        actions: Option<Vec<MpipAction>>,
     ) {
         self.assert_only_creator(mpip_id);
         self.assert_proposal_is_active_or_draft(mpip_id);
@@ -268,6 +319,10 @@ impl MpipContract {
         proposal.data = data;
         proposal.extra = extra;
         self.proposals.insert(&mpip_id, &proposal);
+        // keep current actions if none are sent
+        if let Some(actions) = actions {
+            self.internal_set_proposal_actions(mpip_id, actions);
+        }
     }
 
     pub fn update_meta_vote_contract_address(&mut self, new_meta_vote_contract_address: AccountId) {
@@ -349,6 +404,22 @@ impl MpipContract {
         }
     }
 
+    /// Execution timestamp (eta) of a queued proposal.
+    pub fn get_proposal_eta(&self, mpip_id: MpipId) -> Option<EpochMillis> {
+        self.queued_proposals.get(&mpip_id).map(|(eta, _)| eta)
+    }
+
+    /// After this timestamp a queued proposal can no longer be executed.
+    pub fn get_proposal_expiration(&self, mpip_id: MpipId) -> Option<EpochMillis> {
+        self.queued_proposals
+            .get(&mpip_id)
+            .map(|(_, expires_at)| expires_at)
+    }
+
+    pub fn get_timelock_config(&self) -> (U64, U64) {
+        (self.execution_delay.into(), self.grace_period.into())
+    }
+
     pub fn get_quorum_floor(&self) -> BasisPoints {
         self.quorum_floor
     }
@@ -391,10 +462,13 @@ impl MpipContract {
     ) {
         self.assert_proposal_is_on_voting(&mpip_id);
         self.assert_has_not_voted(mpip_id, env::predecessor_account_id());
+        // snapshot voting: voting power at the start of the voting period
+        let proposal = self.internal_get_proposal(&mpip_id);
+        let snapshot_timestamp = proposal.vote_start_timestamp.unwrap();
         ext_metavote::ext(self.meta_vote_contract_address.clone())
             .with_static_gas(GAS_FOR_GET_VOTING_POWER)
             .with_attached_deposit(1)
-            .get_mpip_voting_power(env::predecessor_account_id())
+            .get_mpip_voting_power_at(env::predecessor_account_id(), U64(snapshot_timestamp))
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_FOR_RESOLVE_VOTE)
@@ -511,18 +585,35 @@ impl MpipContract {
     // * BOT FUNCTIONS *
     // *********
 
+    /// After the voting period ends, queue the proposal if it was accepted.
+    /// The eta counts from the end of the voting, not from this call.
     pub fn process_voting_status(&mut self, mpip_id: MpipId) {
         self.assert_only_operator();
-        if !self.internal_proposal_is_on_voting(&mpip_id) {
-            return;
+        if let MpipState::Accepted = self.internal_get_proposal_state(mpip_id) {
+            let vote_end = self
+                .internal_get_proposal(&mpip_id)
+                .vote_end_timestamp
+                .unwrap();
+            let eta = vote_end + self.execution_delay;
+            self.queued_proposals
+                .insert(&mpip_id, &(eta, eta + self.grace_period));
+            log!("MPIP {} queued, eta {}.", mpip_id, eta);
         }
-        let mut proposal = self.internal_get_proposal(&mpip_id);
-        if get_current_epoch_millis() >= proposal.vote_end_timestamp.unwrap() {
-            if self.internal_is_quorum_reached(mpip_id) {
-                // TODO EXECUTE
-                proposal.executed = true;
-                self.proposals.insert(&mpip_id, &proposal);
+    }
+
+    /// Execute a queued proposal once its eta has passed and before it expires.
+    pub fn execute_proposal(&mut self, mpip_id: MpipId) {
+        match self.internal_get_proposal_state(mpip_id) {
+            MpipState::Queued => {
+                let (eta, _) = self.queued_proposals.get(&mpip_id).unwrap();
+                require!(
+                    get_current_epoch_millis() >= eta,
+                    format!("Proposal is timelocked until {}", eta)
+                );
+                self.internal_execute_proposal(mpip_id);
             }
+            MpipState::Expired => panic!("Proposal expired"),
+            _ => panic!("Proposal is not queued"),
         }
     }
 }
@@ -16,6 +16,8 @@ pub enum MpipState {
     Rejected, // rejected by votes
     Executed, // proposal executed, performing on-chain actions
     Canceled, // canceled by manager after community awareness
+    Queued,   // accepted, waiting for the execution delay (timelock) to pass
+    Expired,  // queued but not executed within the grace period
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -147,6 +149,14 @@ impl MpipContract {
             return MpipState::Active;
         }
 
+        if let Some((_, expires_at)) = self.queued_proposals.get(&mpip_id) {
+            if get_current_epoch_millis() > expires_at {
+                return MpipState::Expired;
+            } else {
+                return MpipState::Queued;
+            }
+        }
+
         if self.internal_is_quorum_reached(mpip_id)
             && self.get_proposal_vote_succeeded(mpip_id)
         {
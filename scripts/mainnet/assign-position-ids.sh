@@ -0,0 +1,17 @@
+#!/bin/bash
+__dir=$(dirname "$0")
+. $__dir/mainnet-set-vars.sh
+
+# Run right after the migration that adds stable locking position ids.
+# Assigns an id to every existing locking position, in batches, from the last voter down
+
+BATCH=${1:-50}
+VOTERS_COUNT=$(NEAR_ENV=mainnet near view $METAVOTE_CONTRACT_ADDRESS get_voters_count | tail -n 1 | tr -d '"')
+echo "Voters: $VOTERS_COUNT, batch: $BATCH"
+
+set -x
+for ((TO=VOTERS_COUNT; TO>0; TO-=BATCH)); do
+  NEAR_ENV=mainnet near call $METAVOTE_CONTRACT_ADDRESS \
+    operator_assign_position_ids '{"to_index":'$TO',"limit":'$BATCH'}' \
+    --useAccount $OPERATOR_ID --gas $TOTAL_PREPAID_GAS
+done
@@ -0,0 +1,16 @@
+#!/bin/bash
+__dir=$(dirname "$0")
+. $__dir/mainnet-set-vars.sh
+
+# Run right after the migration that adds the voters per object index.
+# Indexes the votes cast before the upgrade, in batches, until no voters are left
+
+BATCH=${1:-20}
+
+set -x
+LEFT=1
+while [ "$LEFT" != "0" ]; do
+  LEFT=$(NEAR_ENV=mainnet near call $METAVOTE_CONTRACT_ADDRESS \
+    operator_index_object_voters '{"limit":'$BATCH'}' \
+    --useAccount $OPERATOR_ID --gas $TOTAL_PREPAID_GAS | tail -n 1 | tr -d "'\"")
+done
@@ -0,0 +1,17 @@
+#!/bin/bash
+__dir=$(dirname "$0")
+. $__dir/mainnet-set-vars.sh
+
+# Run after set_vp_curve changes the voting power curve.
+# Recomputes the voting power of every voter locking positions, in batches
+
+BATCH=${1:-20}
+VOTERS_COUNT=$(NEAR_ENV=mainnet near view $METAVOTE_CONTRACT_ADDRESS get_voters_count | tail -n 1 | tr -d '"')
+echo "Voters: $VOTERS_COUNT, batch: $BATCH"
+
+set -x
+for ((FROM=0; FROM<VOTERS_COUNT; FROM+=BATCH)); do
+  NEAR_ENV=mainnet near call $METAVOTE_CONTRACT_ADDRESS \
+    operator_recompute_voting_power '{"from_index":'$FROM',"limit":'$BATCH'}' \
+    --useAccount $OPERATOR_ID --gas $TOTAL_PREPAID_GAS
+done